@@ -303,4 +303,26 @@ contract ChildDataStore {
         // Allow anyone to see vault growth (it's less sensitive)
         return profile.vaultGrowth;
     }
+
+    /**
+     * @notice Get age verification status only (already public on Celo)
+     */
+    function isAgeVerified(address _childAddress) external view returns (bool) {
+        ChildProfile memory profile = childProfiles[_childAddress];
+        if (!profile.exists) revert ProfileNotFound();
+
+        // ScholarFiAgeVerifier.isChildVerified exposes the same flag on Celo
+        return profile.ageVerifiedOnCelo;
+    }
+
+    /**
+     * @notice Get total deposited only (already public on Base and Celo)
+     */
+    function getTotalDeposited(address _childAddress) external view returns (uint256) {
+        ChildProfile memory profile = childProfiles[_childAddress];
+        if (!profile.exists) revert ProfileNotFound();
+
+        // Every deposit is a public FundsDeposited event on Base or Celo
+        return profile.totalDeposited;
+    }
 }
@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.13;
+
+import {Test} from "forge-std/Test.sol";
+import {ChildDataStore} from "../src/ChildDataStore.sol";
+
+contract ChildDataStoreTest is Test {
+    ChildDataStore public store;
+
+    address constant CHILD = address(0xC1);
+    address constant PARENT = address(0xA1);
+    address constant VERIFIER = address(0xCE10);
+
+    function setUp() public {
+        store = new ChildDataStore();
+
+        vm.prank(PARENT);
+        store.createChildProfile(
+            CHILD,
+            "encrypted-name",
+            1_200_000_000,
+            "parent@example.com",
+            address(0xB1),
+            address(0xB2),
+            PARENT,
+            VERIFIER
+        );
+    }
+
+    function test_IsAgeVerified_ReadableWithoutAccess() public {
+        // Sapphire runs unsigned calls from the zero address
+        vm.prank(address(0));
+        assertFalse(store.isAgeVerified(CHILD));
+
+        store.markAgeVerified(CHILD);
+
+        vm.prank(address(0));
+        assertTrue(store.isAgeVerified(CHILD));
+
+        // The rest of the profile stays behind access control
+        vm.prank(address(0));
+        vm.expectRevert(ChildDataStore.Unauthorized.selector);
+        store.getChildProfile(CHILD);
+    }
+
+    function test_IsAgeVerified_RevertsForUnknownChild() public {
+        vm.expectRevert(ChildDataStore.ProfileNotFound.selector);
+        store.isAgeVerified(address(0xDEAD));
+    }
+
+    function test_GetTotalDeposited_ReadableWithoutAccess() public {
+        store.recordDeposit(CHILD, 1 ether);
+        store.recordDeposit(CHILD, 0.5 ether);
+
+        vm.prank(address(0));
+        assertEq(store.getTotalDeposited(CHILD), 1.5 ether);
+    }
+
+    function test_GetTotalDeposited_RevertsForUnknownChild() public {
+        vm.expectRevert(ChildDataStore.ProfileNotFound.selector);
+        store.getTotalDeposited(address(0xDEAD));
+    }
+}
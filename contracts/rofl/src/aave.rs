@@ -0,0 +1,145 @@
+use crate::contracts::AaveProtocolDataProvider;
+use crate::error::{Error, Result};
+use crate::fixed_point::{Ray, Rounding};
+use crate::ledger::SECONDS_PER_YEAR;
+use crate::rpc::RpcClient;
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::SolCall;
+
+/// Current supply rate of one Aave v3 reserve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReserveRate {
+    /// Annual supply rate (APR) as reported by Aave, in ray
+    pub liquidity_rate: Ray,
+    /// `liquidity_rate` compounded every second over a year
+    pub apy: Ray,
+    /// Borrowed share of the supplied liquidity
+    pub utilization: Ray,
+    /// When the reserve's rates were last updated on-chain
+    pub last_update: u64,
+}
+
+impl ReserveRate {
+    /// Per-second rate Aave accrues the supply rate at
+    pub fn rate_per_second(&self) -> Ray {
+        // Dividing by a non-zero constant cannot fail
+        self.liquidity_rate
+            .checked_div_int(U256::from(SECONDS_PER_YEAR), Rounding::Down)
+            .unwrap_or_default()
+    }
+}
+
+/// Reads reserve rates from an Aave v3 PoolDataProvider
+pub struct AaveReserve {
+    data_provider: Address,
+    asset: Address,
+}
+
+impl AaveReserve {
+    pub fn new(data_provider: Address, asset: Address) -> Self {
+        Self { data_provider, asset }
+    }
+
+    /// `getReserveData(asset)`, converted to a compounded APY and utilization
+    pub async fn fetch_rate(&self, rpc: &RpcClient) -> Result<ReserveRate> {
+        let call = AaveProtocolDataProvider::getReserveDataCall { asset: self.asset };
+        let data = rpc.quorum_call(self.data_provider, call.abi_encode().into()).await?;
+        let reserve = AaveProtocolDataProvider::getReserveDataCall::abi_decode_returns(&data)?;
+
+        let liquidity_rate = Ray::from_raw(reserve.liquidityRate);
+        let apy = liquidity_rate
+            .compounded_yield(SECONDS_PER_YEAR)
+            .ok_or_else(|| Error::Overflow("Aave liquidity rate".to_string()))?;
+
+        let total_debt = reserve.totalStableDebt.saturating_add(reserve.totalVariableDebt);
+        let utilization = if reserve.totalAToken.is_zero() {
+            Ray::ZERO
+        } else {
+            Ray::from_raw(total_debt)
+                .checked_div(Ray::from_raw(reserve.totalAToken), Rounding::Down)
+                .ok_or_else(|| Error::Overflow("Aave utilization".to_string()))?
+        };
+
+        Ok(ReserveRate {
+            liquidity_rate,
+            apy,
+            utilization,
+            last_update: reserve.lastUpdateTimestamp.to(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc::MockRpc;
+    use alloy_primitives::{address, aliases::U40, Bytes};
+    use serde_json::{json, Value};
+
+    const PROVIDER: Address = address!("00000000000000000000000000000000000000dd");
+    const ASSET: Address = address!("00000000000000000000000000000000000000ee");
+    const ONE: u128 = 1_000_000_000_000_000_000;
+
+    fn reserve_data(liquidity_rate: Ray, supplied: u128, stable: u128, variable: u128) -> Bytes {
+        let reserve = AaveProtocolDataProvider::getReserveDataReturn {
+            unbacked: U256::ZERO,
+            accruedToTreasuryScaled: U256::ZERO,
+            totalAToken: U256::from(supplied),
+            totalStableDebt: U256::from(stable),
+            totalVariableDebt: U256::from(variable),
+            liquidityRate: liquidity_rate.raw(),
+            variableBorrowRate: Ray::from_bps(700).raw(),
+            stableBorrowRate: U256::ZERO,
+            averageStableBorrowRate: U256::ZERO,
+            liquidityIndex: Ray::ONE.raw(),
+            variableBorrowIndex: Ray::ONE.raw(),
+            lastUpdateTimestamp: U40::from(1_700_000_000u64),
+        };
+        AaveProtocolDataProvider::getReserveDataCall::abi_encode_returns(&reserve).into()
+    }
+
+    async fn rate_from(data: Bytes) -> (ReserveRate, MockRpc) {
+        let node = MockRpc::start(move |method, params: &Value| {
+            assert_eq!(method, "eth_call");
+            assert_eq!(params[0]["to"].as_str().unwrap().parse::<Address>().unwrap(), PROVIDER);
+            let call: Bytes = params[0]["data"].as_str().unwrap().parse().unwrap();
+            let decoded = AaveProtocolDataProvider::getReserveDataCall::abi_decode(&call).unwrap();
+            assert_eq!(decoded.asset, ASSET);
+            Ok(json!(data))
+        })
+        .await;
+        let rpc = RpcClient::new(reqwest::Client::new(), node.url());
+        let rate = AaveReserve::new(PROVIDER, ASSET).fetch_rate(&rpc).await.unwrap();
+        (rate, node)
+    }
+
+    #[tokio::test]
+    async fn converts_liquidity_rate_to_compounded_apy() {
+        // 4% APR compounded per second is ~4.0811% APY
+        let (rate, _node) = rate_from(reserve_data(Ray::from_bps(400), 1_000 * ONE, 0, 600 * ONE)).await;
+
+        assert_eq!(rate.liquidity_rate, Ray::from_bps(400));
+        assert!(rate.apy > Ray::from_bps(408) && rate.apy < Ray::from_bps(409), "{}", rate.apy);
+        assert_eq!(rate.utilization, Ray::from_bps(6_000));
+        assert_eq!(rate.last_update, 1_700_000_000);
+        assert_eq!(
+            rate.rate_per_second().raw(),
+            Ray::from_bps(400).raw() / U256::from(SECONDS_PER_YEAR)
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_reserve_has_zero_utilization() {
+        let (rate, _node) = rate_from(reserve_data(Ray::ZERO, 0, 0, 0)).await;
+
+        assert_eq!(rate.apy, Ray::ZERO);
+        assert_eq!(rate.utilization, Ray::ZERO);
+    }
+
+    #[tokio::test]
+    async fn utilization_counts_stable_and_variable_debt() {
+        let (rate, _node) = rate_from(reserve_data(Ray::from_bps(250), 400 * ONE, 100 * ONE, 100 * ONE)).await;
+
+        assert_eq!(rate.utilization, Ray::from_bps(5_000));
+    }
+}
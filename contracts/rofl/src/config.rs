@@ -0,0 +1,724 @@
+use crate::network::{Network, NetworkProfile};
+use crate::retry::RetryPolicy;
+use crate::signer::Signer;
+use alloy_primitives::Address;
+use reqwest::Url;
+use serde::Deserialize;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+
+/// Validated monitor configuration
+///
+/// Built by `MonitoringConfig::load` from the selected network profile,
+/// an optional TOML file and environment variables, each overriding the
+/// one before. Every field has been checked, so the monitor never starts
+/// pointed at a zero address or a bogus URL.
+#[derive(Debug, Clone)]
+pub struct MonitoringConfig {
+    pub network: Network,
+    /// RPC endpoints per chain, in order of preference
+    pub celo_rpc: Vec<String>,
+    pub oasis_rpc: Vec<String>,
+    pub base_rpc: Vec<String>,
+    /// Celo endpoints that must agree on critical reads
+    pub celo_quorum: u64,
+    /// Celo websocket endpoint for live vault events; polling only without it
+    pub celo_ws_url: Option<String>,
+    pub scholar_fi_vault_address: Address,
+    pub child_data_store_address: Address,
+    pub aave_data_provider_address: Address,
+    pub aave_asset_address: Address,
+    pub vault_deployment_block: u64,
+    /// ScholarFiAgeVerifier on Celo; without it only vault events count
+    /// as verification and there is nothing to reconcile against
+    pub age_verifier_address: Option<Address>,
+    /// How often Celo verification is compared with Oasis profiles; 0 never
+    pub age_reconcile_interval_seconds: u64,
+    /// ParentDepositSplitter on Base; `None` mirrors Celo deposits only
+    pub deposit_splitter_address: Option<Address>,
+    pub splitter_deployment_block: u64,
+    /// ScholarFiBridge on Base; `None` leaves Hyperlane deliveries untracked
+    pub bridge_address: Option<Address>,
+    pub bridge_deployment_block: u64,
+    /// Hyperlane Mailboxes the bridge dispatches from and the vault is
+    /// delivered by
+    pub base_mailbox_address: Option<Address>,
+    pub celo_mailbox_address: Option<Address>,
+    /// Seconds a bridged deposit may stay undelivered before alerting
+    pub delivery_timeout_seconds: u64,
+    /// Added on top of `ScholarFiBridge.estimateFees` in `/fees` quotes
+    pub fee_margin_percent: u64,
+    /// How long a fee read from the bridge is reused
+    pub fee_quote_ttl_seconds: u64,
+    /// `Access-Control-Allow-Origin` of `/fees`: the frontend's origin, or `*`
+    pub fee_cors_origin: String,
+    pub log_page_size: u64,
+    /// Blocks below the head that can still be reorganized; the indexer
+    /// tracks their hashes and treats older blocks as final
+    pub confirmation_depth: u64,
+    /// Multicall3 on Celo; `None` reads each child with its own call
+    pub multicall_address: Option<Address>,
+    /// Child reads per Multicall3 batch; 0 turns batching off
+    pub multicall_batch_size: u64,
+    pub rofl_private_key: Option<SecretKey>,
+    pub tx_timeout_seconds: u64,
+    pub state_path: String,
+    pub check_interval_seconds: u64,
+    /// Where the status server (`/metrics`, `/healthz`, `/readyz`) listens
+    pub http_addr: SocketAddr,
+    /// Signer balance below which `/readyz` fails
+    pub min_signer_balance_wei: u64,
+    /// Tries per RPC request, including the first
+    pub rpc_max_attempts: u64,
+    pub rpc_timeout_seconds: u64,
+    /// First retry delay; doubles per retry up to `rpc_max_backoff_ms`
+    pub rpc_backoff_ms: u64,
+    pub rpc_max_backoff_ms: u64,
+    /// Share of each retry delay that is randomized, 0-100
+    pub rpc_jitter_percent: u64,
+}
+
+/// Private key that never shows up in `Debug` output or logs
+#[derive(Clone)]
+pub struct SecretKey(String);
+
+impl SecretKey {
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretKey(<redacted>)")
+    }
+}
+
+/// Configuration as written in the TOML file, before validation.
+/// Unset fields fall back to environment variables, then to the network
+/// profile, then to defaults.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    network: Option<String>,
+    celo_rpc: Option<RpcUrls>,
+    oasis_rpc: Option<RpcUrls>,
+    base_rpc: Option<RpcUrls>,
+    celo_quorum: Option<u64>,
+    celo_ws_url: Option<String>,
+    scholar_fi_vault_address: Option<String>,
+    child_data_store_address: Option<String>,
+    aave_data_provider_address: Option<String>,
+    aave_asset_address: Option<String>,
+    vault_deployment_block: Option<u64>,
+    age_verifier_address: Option<String>,
+    age_reconcile_interval_seconds: Option<u64>,
+    deposit_splitter_address: Option<String>,
+    splitter_deployment_block: Option<u64>,
+    bridge_address: Option<String>,
+    bridge_deployment_block: Option<u64>,
+    base_mailbox_address: Option<String>,
+    celo_mailbox_address: Option<String>,
+    delivery_timeout_seconds: Option<u64>,
+    fee_margin_percent: Option<u64>,
+    fee_quote_ttl_seconds: Option<u64>,
+    fee_cors_origin: Option<String>,
+    log_page_size: Option<u64>,
+    confirmation_depth: Option<u64>,
+    multicall_address: Option<String>,
+    multicall_batch_size: Option<u64>,
+    rofl_private_key: Option<String>,
+    tx_timeout_seconds: Option<u64>,
+    state_path: Option<String>,
+    check_interval_seconds: Option<u64>,
+    http_addr: Option<String>,
+    min_signer_balance_wei: Option<u64>,
+    rpc_max_attempts: Option<u64>,
+    rpc_timeout_seconds: Option<u64>,
+    rpc_backoff_ms: Option<u64>,
+    rpc_max_backoff_ms: Option<u64>,
+    rpc_jitter_percent: Option<u64>,
+}
+
+/// One RPC URL or a list of them
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RpcUrls {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl RpcUrls {
+    /// Comma-separated, in order of preference
+    fn parse_env(value: &str) -> Self {
+        RpcUrls::Many(value.split(',').map(|url| url.trim().to_string()).collect())
+    }
+
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            RpcUrls::One(url) => vec![url],
+            RpcUrls::Many(urls) => urls,
+        }
+    }
+}
+
+/// Every problem found while loading the configuration
+#[derive(Debug, Default)]
+pub struct ConfigErrors(Vec<String>);
+
+impl ConfigErrors {
+    fn push(&mut self, problem: impl Into<String>) {
+        self.0.push(problem.into());
+    }
+}
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Invalid monitor configuration ({} problems):", self.0.len())?;
+        for problem in &self.0 {
+            writeln!(f, "  - {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+impl MonitoringConfig {
+    /// Load from `path` (if given) with the process environment on top.
+    /// `network` comes from the command line and wins over the file.
+    pub fn load(path: Option<&Path>, network: Option<Network>) -> Result<Self, ConfigErrors> {
+        Self::load_with(path, network, |name| std::env::var(name).ok())
+    }
+
+    /// Load with an explicit environment lookup
+    fn load_with(
+        path: Option<&Path>,
+        network: Option<Network>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigErrors> {
+        let mut errors = ConfigErrors::default();
+
+        let mut raw = match path {
+            Some(path) => match std::fs::read_to_string(path) {
+                Ok(text) => toml::from_str(&text).unwrap_or_else(|e| {
+                    errors.push(format!("{}: {}", path.display(), e));
+                    RawConfig::default()
+                }),
+                Err(e) => {
+                    errors.push(format!("{}: {}", path.display(), e));
+                    RawConfig::default()
+                }
+            },
+            None => RawConfig::default(),
+        };
+
+        raw.apply_env(&env, &mut errors);
+
+        let network = match (network, raw.network.take()) {
+            (Some(network), _) => network,
+            (None, Some(name)) => name.parse().unwrap_or_else(|e| {
+                errors.push(format!("network: {}", e));
+                Network::default()
+            }),
+            (None, None) => Network::default(),
+        };
+        // The contracts' .env names the Sapphire Testnet endpoint this way
+        if network == Network::Testnet && env("OASIS_RPC_URL").is_none() {
+            if let Some(value) = env("SAPPHIRE_TESTNET_RPC") {
+                raw.oasis_rpc = Some(RpcUrls::parse_env(&value));
+            }
+        }
+        let config = raw.validate(network, &mut errors);
+
+        match errors.0.is_empty() {
+            true => Ok(config),
+            false => Err(errors),
+        }
+    }
+
+    /// Retry policy for the monitor's RPC clients
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: self.rpc_max_attempts.try_into().unwrap_or(u32::MAX),
+            base_delay: Duration::from_millis(self.rpc_backoff_ms),
+            max_delay: Duration::from_millis(self.rpc_max_backoff_ms),
+            jitter: self.rpc_jitter_percent as f64 / 100.0,
+            call_timeout: Duration::from_secs(self.rpc_timeout_seconds),
+        }
+    }
+}
+
+impl RawConfig {
+    /// Environment variables override the file
+    fn apply_env(&mut self, env: &impl Fn(&str) -> Option<String>, errors: &mut ConfigErrors) {
+        let string = |name: &str, field: &mut Option<String>| {
+            if let Some(value) = env(name) {
+                *field = Some(value);
+            }
+        };
+        string("ROFL_NETWORK", &mut self.network);
+        let urls = |name: &str, field: &mut Option<RpcUrls>| {
+            if let Some(value) = env(name) {
+                *field = Some(RpcUrls::parse_env(&value));
+            }
+        };
+        urls("CELO_RPC_URL", &mut self.celo_rpc);
+        urls("OASIS_RPC_URL", &mut self.oasis_rpc);
+        urls("BASE_RPC_URL", &mut self.base_rpc);
+        string("SCHOLAR_FI_VAULT", &mut self.scholar_fi_vault_address);
+        string("CHILD_DATA_STORE", &mut self.child_data_store_address);
+        string("AAVE_DATA_PROVIDER", &mut self.aave_data_provider_address);
+        string("AAVE_ASSET", &mut self.aave_asset_address);
+        string("CELO_WS_URL", &mut self.celo_ws_url);
+        string("MULTICALL_ADDRESS", &mut self.multicall_address);
+        string("DEPOSIT_SPLITTER", &mut self.deposit_splitter_address);
+        string("AGE_VERIFIER", &mut self.age_verifier_address);
+        string("SCHOLAR_FI_BRIDGE", &mut self.bridge_address);
+        string("BASE_MAILBOX", &mut self.base_mailbox_address);
+        string("CELO_MAILBOX", &mut self.celo_mailbox_address);
+        string("ROFL_PRIVATE_KEY", &mut self.rofl_private_key);
+        string("STATE_DB", &mut self.state_path);
+        string("HTTP_ADDR", &mut self.http_addr);
+        string("FEE_CORS_ORIGIN", &mut self.fee_cors_origin);
+
+        let mut number = |name: &str, field: &mut Option<u64>| {
+            if let Some(value) = env(name) {
+                match value.trim().parse() {
+                    Ok(n) => *field = Some(n),
+                    Err(_) => errors.push(format!("{}: expected a non-negative integer, got {:?}", name, value)),
+                }
+            }
+        };
+        number("CELO_QUORUM", &mut self.celo_quorum);
+        number("VAULT_DEPLOYMENT_BLOCK", &mut self.vault_deployment_block);
+        number("SPLITTER_DEPLOYMENT_BLOCK", &mut self.splitter_deployment_block);
+        number("AGE_RECONCILE_INTERVAL", &mut self.age_reconcile_interval_seconds);
+        number("BRIDGE_DEPLOYMENT_BLOCK", &mut self.bridge_deployment_block);
+        number("DELIVERY_TIMEOUT", &mut self.delivery_timeout_seconds);
+        number("FEE_MARGIN_PERCENT", &mut self.fee_margin_percent);
+        number("FEE_QUOTE_TTL", &mut self.fee_quote_ttl_seconds);
+        number("LOG_PAGE_SIZE", &mut self.log_page_size);
+        number("CONFIRMATION_DEPTH", &mut self.confirmation_depth);
+        number("MULTICALL_BATCH_SIZE", &mut self.multicall_batch_size);
+        number("TX_TIMEOUT", &mut self.tx_timeout_seconds);
+        number("CHECK_INTERVAL", &mut self.check_interval_seconds);
+        number("MIN_SIGNER_BALANCE", &mut self.min_signer_balance_wei);
+        number("RPC_MAX_ATTEMPTS", &mut self.rpc_max_attempts);
+        number("RPC_TIMEOUT", &mut self.rpc_timeout_seconds);
+        number("RPC_BACKOFF_MS", &mut self.rpc_backoff_ms);
+        number("RPC_MAX_BACKOFF_MS", &mut self.rpc_max_backoff_ms);
+        number("RPC_JITTER_PERCENT", &mut self.rpc_jitter_percent);
+    }
+
+    fn validate(self, network: Network, errors: &mut ConfigErrors) -> MonitoringConfig {
+        let profile: &NetworkProfile = network.profile();
+        let known = |address: Option<Address>| address.map(|a| a.to_string());
+
+        let rofl_private_key = self.rofl_private_key.filter(|key| !key.trim().is_empty());
+        if let Some(key) = &rofl_private_key {
+            if Signer::from_hex(key).is_err() {
+                errors.push("rofl_private_key: not a valid 32-byte hex secp256k1 key");
+            }
+        }
+
+        let http_addr = self.http_addr.as_deref().unwrap_or("127.0.0.1:9100");
+        let http_addr = http_addr.trim().parse().unwrap_or_else(|_| {
+            errors.push(format!("http_addr: expected an IP:port address, got {:?}", http_addr));
+            SocketAddr::from(([127, 0, 0, 1], 9100))
+        });
+
+        let defaults = RetryPolicy::default();
+        let rpc_backoff_ms = self.rpc_backoff_ms.unwrap_or(defaults.base_delay.as_millis() as u64);
+        let rpc_max_backoff_ms = self.rpc_max_backoff_ms.unwrap_or(defaults.max_delay.as_millis() as u64);
+        if rpc_max_backoff_ms < rpc_backoff_ms {
+            errors.push(format!(
+                "rpc_max_backoff_ms: {} is below rpc_backoff_ms ({})",
+                rpc_max_backoff_ms, rpc_backoff_ms
+            ));
+        }
+        let rpc_jitter_percent = self.rpc_jitter_percent.unwrap_or((defaults.jitter * 100.0) as u64);
+        if rpc_jitter_percent > 100 {
+            errors.push(format!("rpc_jitter_percent: must be at most 100, got {}", rpc_jitter_percent));
+        }
+
+        let urls = |value: Option<RpcUrls>, default: &str| value.map_or_else(|| vec![default.to_string()], RpcUrls::into_vec);
+        let celo_rpc = rpc_urls("celo_rpc", urls(self.celo_rpc, profile.celo.rpc), errors);
+        let oasis_rpc = rpc_urls("oasis_rpc", urls(self.oasis_rpc, profile.oasis.rpc), errors);
+        let base_rpc = rpc_urls("base_rpc", urls(self.base_rpc, profile.base.rpc), errors);
+
+        // Default to a simple majority of the configured endpoints
+        let celo_quorum = positive("celo_quorum", self.celo_quorum.unwrap_or(celo_rpc.len() as u64 / 2 + 1), errors);
+        if celo_quorum > celo_rpc.len() as u64 && !celo_rpc.is_empty() {
+            errors.push(format!(
+                "celo_quorum: {} exceeds the {} configured celo_rpc endpoints",
+                celo_quorum,
+                celo_rpc.len()
+            ));
+        }
+
+        let state_path = self.state_path.unwrap_or_else(|| "rofl-state.db".to_string());
+        if state_path.trim().is_empty() {
+            errors.push("state_path: must not be empty");
+        }
+
+        let bridge_address = self
+            .bridge_address
+            .map(|value| address("bridge_address", Some(value), errors));
+        let base_mailbox_address = match self.base_mailbox_address {
+            Some(value) => Some(address("base_mailbox_address", Some(value), errors)),
+            None => profile.base.mailbox,
+        };
+        let celo_mailbox_address = match self.celo_mailbox_address {
+            Some(value) => Some(address("celo_mailbox_address", Some(value), errors)),
+            None => profile.celo.mailbox,
+        };
+        if bridge_address.is_some() && (base_mailbox_address.is_none() || celo_mailbox_address.is_none()) {
+            errors.push("bridge_address: tracking deliveries needs base_mailbox_address and celo_mailbox_address");
+        }
+
+        MonitoringConfig {
+            network,
+            celo_rpc,
+            oasis_rpc,
+            base_rpc,
+            celo_quorum,
+            celo_ws_url: self
+                .celo_ws_url
+                .filter(|url| !url.trim().is_empty())
+                .map(|url| ws_url("celo_ws_url", url, errors)),
+            scholar_fi_vault_address: address(
+                "scholar_fi_vault_address",
+                self.scholar_fi_vault_address.or(known(profile.deployment.scholar_fi_vault)),
+                errors,
+            ),
+            child_data_store_address: address(
+                "child_data_store_address",
+                self.child_data_store_address.or(known(profile.deployment.child_data_store)),
+                errors,
+            ),
+            aave_data_provider_address: address("aave_data_provider_address", self.aave_data_provider_address, errors),
+            aave_asset_address: address("aave_asset_address", self.aave_asset_address, errors),
+            vault_deployment_block: self.vault_deployment_block.unwrap_or(0),
+            age_verifier_address: match self.age_verifier_address {
+                Some(value) => Some(address("age_verifier_address", Some(value), errors)),
+                None => profile.deployment.age_verifier,
+            },
+            age_reconcile_interval_seconds: self.age_reconcile_interval_seconds.unwrap_or(6 * 3600),
+            deposit_splitter_address: match self.deposit_splitter_address {
+                Some(value) => Some(address("deposit_splitter_address", Some(value), errors)),
+                None => profile.deployment.deposit_splitter,
+            },
+            splitter_deployment_block: self.splitter_deployment_block.unwrap_or(0),
+            bridge_address,
+            bridge_deployment_block: self.bridge_deployment_block.unwrap_or(0),
+            base_mailbox_address,
+            celo_mailbox_address,
+            // Hyperlane relays within minutes; half an hour means it is stuck
+            delivery_timeout_seconds: positive(
+                "delivery_timeout_seconds",
+                self.delivery_timeout_seconds.unwrap_or(1800),
+                errors,
+            ),
+            // Covers the interchain gas price moving between quote and send
+            fee_margin_percent: self.fee_margin_percent.unwrap_or(10),
+            fee_quote_ttl_seconds: positive("fee_quote_ttl_seconds", self.fee_quote_ttl_seconds.unwrap_or(30), errors),
+            // Quotes are public and carry no credentials
+            fee_cors_origin: cors_origin("fee_cors_origin", self.fee_cors_origin.unwrap_or("*".to_string()), errors),
+            // Stay under typical eth_getLogs range limits
+            log_page_size: positive("log_page_size", self.log_page_size.unwrap_or(5000), errors),
+            // Celo blocks are final after one, but L2 sequencers and
+            // misbehaving RPC replicas can still hand out stale forks
+            confirmation_depth: positive("confirmation_depth", self.confirmation_depth.unwrap_or(32), errors),
+            multicall_address: match self.multicall_address {
+                Some(value) => Some(address("multicall_address", Some(value), errors)),
+                None => profile.celo.multicall3,
+            },
+            // Well under the gas and response size limits of public RPCs
+            multicall_batch_size: self.multicall_batch_size.unwrap_or(100),
+            rofl_private_key: rofl_private_key.map(SecretKey),
+            tx_timeout_seconds: positive("tx_timeout_seconds", self.tx_timeout_seconds.unwrap_or(120), errors),
+            state_path,
+            // Default: check every hour
+            check_interval_seconds: positive("check_interval_seconds", self.check_interval_seconds.unwrap_or(3600), errors),
+            http_addr,
+            // 0.1 ROSE covers a few hundred growth updates
+            min_signer_balance_wei: self.min_signer_balance_wei.unwrap_or(100_000_000_000_000_000),
+            rpc_max_attempts: positive(
+                "rpc_max_attempts",
+                self.rpc_max_attempts.unwrap_or(defaults.max_attempts.into()),
+                errors,
+            ),
+            rpc_timeout_seconds: positive(
+                "rpc_timeout_seconds",
+                self.rpc_timeout_seconds.unwrap_or(defaults.call_timeout.as_secs()),
+                errors,
+            ),
+            rpc_backoff_ms,
+            rpc_max_backoff_ms,
+            rpc_jitter_percent,
+        }
+    }
+}
+
+fn address(field: &str, value: Option<String>, errors: &mut ConfigErrors) -> Address {
+    let Some(value) = value else {
+        errors.push(format!("{}: missing", field));
+        return Address::ZERO;
+    };
+    match value.trim().parse::<Address>() {
+        Ok(Address::ZERO) => {
+            errors.push(format!("{}: zero address", field));
+            Address::ZERO
+        }
+        Ok(address) => address,
+        Err(_) => {
+            errors.push(format!("{}: malformed address {:?}", field, value));
+            Address::ZERO
+        }
+    }
+}
+
+fn rpc_urls(field: &str, values: Vec<String>, errors: &mut ConfigErrors) -> Vec<String> {
+    if values.is_empty() {
+        errors.push(format!("{}: needs at least one endpoint", field));
+    }
+    values.into_iter().map(|value| rpc_url(field, value, errors)).collect()
+}
+
+fn rpc_url(field: &str, value: String, errors: &mut ConfigErrors) -> String {
+    match Url::parse(value.trim()) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.host().is_some() => value.trim().to_string(),
+        Ok(url) => {
+            errors.push(format!("{}: unsupported URL {:?} (expected http or https, got {})", field, value, url.scheme()));
+            value
+        }
+        Err(e) => {
+            errors.push(format!("{}: invalid URL {:?} ({})", field, value, e));
+            value
+        }
+    }
+}
+
+fn ws_url(field: &str, value: String, errors: &mut ConfigErrors) -> String {
+    match Url::parse(value.trim()) {
+        Ok(url) if matches!(url.scheme(), "ws" | "wss") && url.host().is_some() => value.trim().to_string(),
+        Ok(url) => {
+            errors.push(format!("{}: unsupported URL {:?} (expected ws or wss, got {})", field, value, url.scheme()));
+            value
+        }
+        Err(e) => {
+            errors.push(format!("{}: invalid URL {:?} ({})", field, value, e));
+            value
+        }
+    }
+}
+
+/// `*`, or an http(s) origin such as `https://app.example`
+fn cors_origin(field: &str, value: String, errors: &mut ConfigErrors) -> String {
+    let value = value.trim();
+    if value == "*" {
+        return value.to_string();
+    }
+    match Url::parse(value) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.host().is_some() && url.path() == "/" => {
+            url.origin().ascii_serialization()
+        }
+        _ => {
+            errors.push(format!("{}: expected * or an origin like https://app.example, got {:?}", field, value));
+            value.to_string()
+        }
+    }
+}
+
+fn positive(field: &str, value: u64, errors: &mut ConfigErrors) -> u64 {
+    if value == 0 {
+        errors.push(format!("{}: must be greater than zero", field));
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::Write;
+
+    const VALID: &str = r#"
+        celo_rpc = "https://forno.celo-sepolia.celo-testnet.org"
+        oasis_rpc = "https://testnet.sapphire.oasis.io"
+        scholar_fi_vault_address = "0x00000000000000000000000000000000000000aa"
+        child_data_store_address = "0x0D045460DBfE3A17DD2eA21f4c4cA193a1deF25E"
+        aave_data_provider_address = "0x00000000000000000000000000000000000000dd"
+        aave_asset_address = "0x00000000000000000000000000000000000000ee"
+        check_interval_seconds = 600
+    "#;
+
+    fn write_toml(contents: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file
+    }
+
+    fn load(contents: &str, env: &[(&str, &str)]) -> Result<MonitoringConfig, ConfigErrors> {
+        let file = write_toml(contents);
+        let env: HashMap<String, String> = env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        MonitoringConfig::load_with(Some(file.path()), None, |name| env.get(name).cloned())
+    }
+
+    #[test]
+    fn loads_file_with_defaults() {
+        let config = load(VALID, &[]).unwrap();
+
+        assert_eq!(config.check_interval_seconds, 600);
+        assert_eq!(config.log_page_size, 5000);
+        assert_eq!(config.tx_timeout_seconds, 120);
+        assert_eq!(config.state_path, "rofl-state.db");
+        assert!(config.rofl_private_key.is_none());
+        assert_eq!(config.retry_policy(), RetryPolicy::default());
+    }
+
+    #[test]
+    fn env_overrides_file() {
+        let config = load(
+            VALID,
+            &[("CELO_RPC_URL", "http://127.0.0.1:8545"), ("CHECK_INTERVAL", "30")],
+        )
+        .unwrap();
+
+        assert_eq!(config.celo_rpc, ["http://127.0.0.1:8545"]);
+        assert_eq!(config.check_interval_seconds, 30);
+    }
+
+    #[test]
+    fn accepts_endpoint_lists() {
+        let config = load(
+            &format!("{}
+base_rpc = [\"https://a.example\", \"https://b.example\"]", VALID),
+            &[("CELO_RPC_URL", "https://a.example, https://b.example,https://c.example")],
+        )
+        .unwrap();
+        assert_eq!(config.celo_rpc, ["https://a.example", "https://b.example", "https://c.example"]);
+        assert_eq!(config.celo_quorum, 2, "defaults to a majority");
+        assert_eq!(config.base_rpc.len(), 2);
+        assert_eq!(config.oasis_rpc, ["https://testnet.sapphire.oasis.io"]);
+
+        let errors = load(VALID, &[("CELO_QUORUM", "2")]).unwrap_err();
+        assert!(errors.to_string().contains("celo_quorum: 2 exceeds the 1 configured celo_rpc endpoints"));
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let errors = load(
+            r#"
+            celo_rpc = "ftp://example.com"
+            oasis_rpc = "not a url"
+            scholar_fi_vault_address = "0x0000000000000000000000000000000000000000"
+            child_data_store_address = "0x1234"
+            aave_data_provider_address = "0x00000000000000000000000000000000000000dd"
+            check_interval_seconds = 0
+            "#,
+            &[
+                ("ROFL_PRIVATE_KEY", "0xdeadbeef"),
+                ("LOG_PAGE_SIZE", "lots"),
+                ("HTTP_ADDR", "localhost"),
+                ("RPC_JITTER_PERCENT", "150"),
+                ("CELO_WS_URL", "https://forno.celo.org/ws"),
+                ("FEE_CORS_ORIGIN", "app.example"),
+            ],
+        )
+        .unwrap_err();
+
+        let report = errors.to_string();
+        for expected in [
+            "celo_rpc: unsupported URL",
+            "oasis_rpc: invalid URL",
+            "scholar_fi_vault_address: zero address",
+            "child_data_store_address: malformed address",
+            "aave_asset_address: missing",
+            "check_interval_seconds: must be greater than zero",
+            "rofl_private_key: not a valid",
+            "LOG_PAGE_SIZE: expected a non-negative integer",
+            "http_addr: expected an IP:port address",
+            "rpc_jitter_percent: must be at most 100",
+            "celo_ws_url: unsupported URL",
+            "fee_cors_origin: expected * or an origin",
+        ] {
+            assert!(report.contains(expected), "missing {:?} in:\n{}", expected, report);
+        }
+        assert_eq!(errors.0.len(), 12);
+        assert!(!report.contains("deadbeef"), "private key leaked into report");
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_missing_file() {
+        let errors = load(&format!("{}\nchek_interval = 5", VALID), &[]).unwrap_err();
+        assert!(errors.to_string().contains("unknown field `chek_interval`"));
+
+        let errors = MonitoringConfig::load_with(Some(Path::new("/nonexistent/rofl.toml")), None, |_| None).unwrap_err();
+        assert!(errors.to_string().contains("/nonexistent/rofl.toml"));
+    }
+
+    #[test]
+    fn network_profile_fills_defaults() {
+        let minimal = r#"
+            scholar_fi_vault_address = "0x00000000000000000000000000000000000000aa"
+            aave_data_provider_address = "0x00000000000000000000000000000000000000dd"
+            aave_asset_address = "0x00000000000000000000000000000000000000ee"
+        "#;
+        let config = load(&format!("{}\nchild_data_store_address = \"0x00000000000000000000000000000000000000cc\"", minimal), &[]).unwrap();
+        let testnet = Network::Testnet.profile();
+        assert_eq!(config.network, Network::Testnet);
+        assert_eq!(config.fee_cors_origin, "*");
+        assert_eq!(config.celo_rpc, [testnet.celo.rpc]);
+        assert_eq!(config.base_rpc, [testnet.base.rpc]);
+        assert_eq!(config.age_verifier_address, testnet.deployment.age_verifier);
+        assert_eq!(config.base_mailbox_address, testnet.base.mailbox);
+
+        // No profile knows a data store with the views the monitor reads,
+        // so it must be configured
+        for network in [Network::Testnet, Network::Mainnet] {
+            let file = write_toml(minimal);
+            let errors = MonitoringConfig::load_with(Some(file.path()), Some(network), |_| None).unwrap_err();
+            assert!(errors.to_string().contains("child_data_store_address: missing"));
+        }
+
+        // The command line wins over the file
+        let file = write_toml(&format!("network = \"mainnet\"\n{}", VALID));
+        let config = MonitoringConfig::load_with(Some(file.path()), Some(Network::Local), |_| None).unwrap();
+        assert_eq!(config.network, Network::Local);
+        assert_eq!(config.oasis_rpc, ["https://testnet.sapphire.oasis.io"], "file still overrides the profile");
+    }
+
+    #[test]
+    fn sapphire_testnet_rpc_only_applies_to_testnet() {
+        let env = |name: &str| (name == "SAPPHIRE_TESTNET_RPC").then(|| "https://testnet.example".to_string());
+        let file = write_toml(
+            r#"
+            scholar_fi_vault_address = "0x00000000000000000000000000000000000000aa"
+            child_data_store_address = "0x00000000000000000000000000000000000000cc"
+            aave_data_provider_address = "0x00000000000000000000000000000000000000dd"
+            aave_asset_address = "0x00000000000000000000000000000000000000ee"
+            "#,
+        );
+        let testnet = MonitoringConfig::load_with(Some(file.path()), Some(Network::Testnet), env).unwrap();
+        assert_eq!(testnet.oasis_rpc, ["https://testnet.example"]);
+        let mainnet = MonitoringConfig::load_with(Some(file.path()), Some(Network::Mainnet), env).unwrap();
+        assert_eq!(mainnet.oasis_rpc, [Network::Mainnet.profile().oasis.rpc]);
+
+        let config = load(VALID, &[("OASIS_RPC_URL", "https://a.example"), ("SAPPHIRE_TESTNET_RPC", "https://b.example")]);
+        assert_eq!(config.unwrap().oasis_rpc, ["https://a.example"]);
+    }
+
+    #[test]
+    fn secret_key_is_redacted() {
+        let config = load(
+            VALID,
+            &[("ROFL_PRIVATE_KEY", "0x4646464646464646464646464646464646464646464646464646464646464646")],
+        )
+        .unwrap();
+        assert!(!format!("{:?}", config).contains("4646"));
+    }
+}
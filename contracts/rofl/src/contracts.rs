@@ -0,0 +1,224 @@
+use alloy_sol_types::sol;
+
+// ABI bindings for the Scholar-Fi contracts the monitor talks to.
+// Only the functions, events and errors the monitor uses are declared here;
+// signatures must match the Solidity sources under contracts/.
+
+sol! {
+    /// ScholarFiVault on Celo (contracts/celo/src/ScholarFiVault.sol)
+    interface ScholarFiVault {
+        event ChildAccountCreated(
+            address indexed child,
+            address indexed parent,
+            uint256 timestamp
+        );
+
+        event FundsDeposited(
+            address indexed child,
+            address indexed parent,
+            uint256 totalAmount,
+            uint256 vaultAmount,
+            uint256 spendingAmount
+        );
+
+        event VaultUnlocked(
+            address indexed child,
+            uint256 amount,
+            uint256 timestamp
+        );
+
+        event WhitelistPayment(
+            address indexed child,
+            address indexed recipient,
+            uint256 amount
+        );
+
+        /// ISelfVerificationRoot.GenericDiscloseOutputV2 from @selfxyz/contracts;
+        /// only needed for the event signature
+        struct GenericDiscloseOutputV2 {
+            bytes32 attestationId;
+            uint256 userIdentifier;
+            uint256 nullifier;
+            uint256[4] forbiddenCountriesListPacked;
+            string issuingState;
+            string[] name;
+            string idNumber;
+            string nationality;
+            string dateOfBirth;
+            string gender;
+            string expiryDate;
+            uint256 olderThan;
+            bool[3] ofac;
+        }
+
+        event AgeVerificationCompleted(
+            address indexed child,
+            GenericDiscloseOutputV2 output
+        );
+
+        error AccountNotFound();
+        error NotParent();
+        error ZeroAmount();
+        error NotMailbox();
+        error UnauthorizedSender();
+
+        /// Hyperlane recipient hook; only the Celo Mailbox may call it
+        function handle(uint32 _origin, bytes32 _sender, bytes calldata _messageBody) external payable;
+
+        function getChildAccount(address _child) external view returns (
+            address childWallet,
+            address parentWallet,
+            uint256 vaultBalance,
+            uint256 spendingBalance,
+            bool isVerified,
+            uint256 createdAt
+        );
+
+        function getVerificationConfig() external view returns (bytes32 configId, uint256 olderThan);
+        function getHyperlaneConfig() external view returns (address mailboxAddress, address authorizedBridge);
+    }
+}
+
+sol! {
+    /// ChildDataStore on Oasis Sapphire (contracts/oasis/src/ChildDataStore.sol)
+    interface ChildDataStore {
+        error ProfileNotFound();
+        error Unauthorized();
+
+        function updateVaultGrowth(address _childAddress, uint256 _newGrowth) external;
+        function recordDeposit(address _childAddress, uint256 _additionalDeposit) external;
+        function getVaultGrowth(address _childAddress) external view returns (uint256);
+        function markAgeVerified(address _childAddress) external;
+        function isAgeVerified(address _childAddress) external view returns (bool);
+        function getTotalDeposited(address _childAddress) external view returns (uint256);
+        function getChildProfile(address _childAddress) external view returns (
+            string memory encryptedName,
+            uint256 dateOfBirth,
+            string memory parentEmail,
+            address baseCheckingWallet,
+            address baseVaultWallet,
+            address parentBaseWallet,
+            bool ageVerifiedOnCelo,
+            uint256 totalDeposited,
+            uint256 vaultGrowth,
+            uint256 lastUpdated
+        );
+    }
+}
+
+sol! {
+    /// ScholarFiAgeVerifier on Celo (contracts/celo/src/ScholarFiAgeVerifier.sol)
+    interface ScholarFiAgeVerifier {
+        /// Same struct as in ScholarFiVault; only needed for the event signature
+        struct GenericDiscloseOutputV2 {
+            bytes32 attestationId;
+            uint256 userIdentifier;
+            uint256 nullifier;
+            uint256[4] forbiddenCountriesListPacked;
+            string issuingState;
+            string[] name;
+            string idNumber;
+            string nationality;
+            string dateOfBirth;
+            string gender;
+            string expiryDate;
+            uint256 olderThan;
+            bool[3] ofac;
+        }
+
+        event ChildVerified(
+            address indexed childAddress,
+            address indexed parentAddress,
+            uint256 timestamp,
+            GenericDiscloseOutputV2 output
+        );
+
+        function isChildVerified(address childAddress) external view returns (bool);
+        function getVerificationConfig() external view returns (bytes32 configId, uint256 minAge);
+    }
+}
+
+sol! {
+    /// ParentDepositSplitter on Base (contracts/base/src/ParentDepositSplitter.sol)
+    interface ParentDepositSplitter {
+        event FundsDeposited(
+            address indexed childAddress,
+            address indexed parentWallet,
+            uint256 totalAmount,
+            uint256 vaultAmount,
+            uint256 checkingAmount,
+            uint256 timestamp
+        );
+    }
+}
+
+sol! {
+    /// ScholarFiBridge on Base (contracts/base/src/ScholarFiBridge.sol)
+    interface ScholarFiBridge {
+        /// Hyperlane fee for a deposit to `childWallet`; the message body
+        /// names `msg.sender` as the parent
+        function estimateFees(address childWallet, uint256 amount) external view returns (uint256 fees);
+        function getConfig() external view returns (address mailbox, uint32 celoDomain, address celoVaultAddress);
+    }
+}
+
+sol! {
+    /// Hyperlane v3 Mailbox (@hyperlane-xyz/core IMailbox), on Base and Celo
+    interface IMailbox {
+        event Dispatch(
+            address indexed sender,
+            uint32 indexed destination,
+            bytes32 indexed recipient,
+            bytes message
+        );
+
+        event DispatchId(bytes32 indexed messageId);
+
+        event Process(
+            uint32 indexed origin,
+            bytes32 indexed sender,
+            address indexed recipient
+        );
+
+        event ProcessId(bytes32 indexed messageId);
+    }
+}
+
+sol! {
+    /// Aave v3 AaveProtocolDataProvider ("PoolDataProvider") on Celo
+    interface AaveProtocolDataProvider {
+        function getReserveData(address asset) external view returns (
+            uint256 unbacked,
+            uint256 accruedToTreasuryScaled,
+            uint256 totalAToken,
+            uint256 totalStableDebt,
+            uint256 totalVariableDebt,
+            uint256 liquidityRate,
+            uint256 variableBorrowRate,
+            uint256 stableBorrowRate,
+            uint256 averageStableBorrowRate,
+            uint256 liquidityIndex,
+            uint256 variableBorrowIndex,
+            uint40 lastUpdateTimestamp
+        );
+    }
+}
+
+sol! {
+    /// Multicall3 (github.com/mds1/multicall), deployed at the same address
+    /// on every chain that has it
+    interface IMulticall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Result {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls) external payable returns (Result[] memory returnData);
+    }
+}
@@ -0,0 +1,282 @@
+use crate::contracts::ChildDataStore::{self, ChildDataStoreErrors};
+use crate::error::{Error, Result};
+use crate::rpc::{RpcClient, TransactionReceipt};
+use crate::tx::TxSender;
+use alloy_primitives::{Address, B256, U256};
+use alloy_sol_types::SolCall;
+
+/// ChildDataStore on Oasis Sapphire
+///
+/// Writes need a `TxSender`; without one the store is read-only and the
+/// monitor runs in dry-run mode. Reverts come back as `Error::Revert`
+/// named after the ChildDataStore custom error (`ProfileNotFound`,
+/// `Unauthorized`) when the selector is known.
+pub struct DataStore {
+    address: Address,
+    rpc: RpcClient,
+    sender: Option<TxSender>,
+}
+
+impl DataStore {
+    pub fn new(address: Address, rpc: RpcClient, sender: Option<TxSender>) -> Self {
+        Self { address, rpc, sender }
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Address writes are sent from, if a signer is configured
+    pub fn signer(&self) -> Option<Address> {
+        self.sender.as_ref().map(TxSender::address)
+    }
+
+    pub fn sender(&self) -> Option<&TxSender> {
+        self.sender.as_ref()
+    }
+
+    fn writer(&self) -> Result<&TxSender> {
+        self.sender
+            .as_ref()
+            .ok_or_else(|| Error::Signer("no signer configured for ChildDataStore writes".to_string()))
+    }
+
+    /// Check transactions left pending by earlier cycles. Returns those
+    /// that reverted or were dropped.
+    pub async fn reconcile_pending(&self) -> Result<Vec<B256>> {
+        match &self.sender {
+            Some(sender) => sender.reconcile_pending(&self.rpc).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Cumulative growth currently stored for `child`
+    pub async fn vault_growth(&self, child: Address) -> Result<U256> {
+        let call = ChildDataStore::getVaultGrowthCall { _childAddress: child };
+        let data = self
+            .rpc
+            .eth_call(self.address, call.abi_encode().into())
+            .await
+            .map_err(|e| e.name_revert(ChildDataStoreErrors::name_by_selector))?;
+        Ok(ChildDataStore::getVaultGrowthCall::abi_decode_returns(&data)?)
+    }
+
+    /// Sum of the deposits recorded for `child`, read with the access-free
+    /// `getTotalDeposited` for the same reason as `age_verified`
+    pub async fn total_deposited(&self, child: Address) -> Result<U256> {
+        let call = ChildDataStore::getTotalDepositedCall { _childAddress: child };
+        let data = self
+            .rpc
+            .eth_call(self.address, call.abi_encode().into())
+            .await
+            .map_err(|e| e.name_revert(ChildDataStoreErrors::name_by_selector))?;
+        Ok(ChildDataStore::getTotalDepositedCall::abi_decode_returns(&data)?)
+    }
+
+    /// Whether the child's profile has `ageVerifiedOnCelo` set. Read with
+    /// the access-free `isAgeVerified`, since Sapphire runs unsigned calls
+    /// from the zero address, which `getChildProfile` rejects.
+    pub async fn age_verified(&self, child: Address) -> Result<bool> {
+        let call = ChildDataStore::isAgeVerifiedCall { _childAddress: child };
+        let data = self
+            .rpc
+            .eth_call(self.address, call.abi_encode().into())
+            .await
+            .map_err(|e| e.name_revert(ChildDataStoreErrors::name_by_selector))?;
+        Ok(ChildDataStore::isAgeVerifiedCall::abi_decode_returns(&data)?)
+    }
+
+    pub async fn update_vault_growth(
+        &self,
+        child: Address,
+        growth: U256,
+    ) -> Result<TransactionReceipt> {
+        let call = ChildDataStore::updateVaultGrowthCall {
+            _childAddress: child,
+            _newGrowth: growth,
+        };
+        self.send(call.abi_encode()).await
+    }
+
+    /// Submit `recordDeposit(child, amount)`, adding `amount` to the
+    /// child's `totalDeposited`, without waiting for it. The call is not
+    /// idempotent, so the caller must keep the hash before anything else
+    /// can fail, and wait with `confirm_record_deposit`.
+    pub async fn record_deposit(&self, child: Address, amount: U256) -> Result<B256> {
+        let data = record_deposit_call(child, amount);
+        self.writer()?
+            .submit(&self.rpc, self.address, data.into())
+            .await
+            .map_err(|e| e.name_revert(ChildDataStoreErrors::name_by_selector))
+    }
+
+    /// Wait for a `recordDeposit` submitted with `record_deposit`
+    pub async fn confirm_record_deposit(&self, hash: B256, child: Address, amount: U256) -> Result<TransactionReceipt> {
+        let data = record_deposit_call(child, amount);
+        self.writer()?
+            .confirm(&self.rpc, hash, self.address, data.into())
+            .await
+            .map_err(|e| e.name_revert(ChildDataStoreErrors::name_by_selector))
+    }
+
+    /// Call `markAgeVerified(child)` and wait for the receipt
+    pub async fn mark_age_verified(&self, child: Address) -> Result<TransactionReceipt> {
+        let call = ChildDataStore::markAgeVerifiedCall { _childAddress: child };
+        self.send(call.abi_encode()).await
+    }
+
+    async fn send(&self, data: Vec<u8>) -> Result<TransactionReceipt> {
+        self.writer()?
+            .send(&self.rpc, self.address, data.into())
+            .await
+            .map_err(|e| e.name_revert(ChildDataStoreErrors::name_by_selector))
+    }
+}
+
+fn record_deposit_call(child: Address, amount: U256) -> Vec<u8> {
+    ChildDataStore::recordDepositCall {
+        _childAddress: child,
+        _additionalDeposit: amount,
+    }
+    .abi_encode()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc::{reverted, MockRpc};
+    use crate::rpc::JsonRpcError;
+    use crate::signer::Signer;
+    use alloy_primitives::{address, hex, keccak256, Bytes};
+    use alloy_sol_types::SolError;
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const STORE: Address = address!("00000000000000000000000000000000000000cc");
+    const CHILD: Address = address!("0000000000000000000000000000000000000001");
+
+    /// Canned Sapphire node; `estimate` and `replay` decide the simulated
+    /// outcome, `status` the mined one. The first receipt poll sees a pending tx.
+    fn oasis_node(
+        estimate: Result<Value, JsonRpcError>,
+        status: &'static str,
+        replay: Result<Value, JsonRpcError>,
+    ) -> impl Fn(&str, &Value) -> Result<Value, JsonRpcError> + Send + Sync + 'static {
+        let polls = Arc::new(AtomicUsize::new(0));
+        move |method, params| match method {
+            "eth_estimateGas" => estimate.clone(),
+            "eth_chainId" => Ok(json!("0x5aff")),
+            "eth_getTransactionCount" => Ok(json!("0x7")),
+            "eth_gasPrice" => Ok(json!("0x174876e800")),
+            "eth_sendRawTransaction" => {
+                let raw: Bytes = params[0].as_str().unwrap().parse().unwrap();
+                Ok(json!(keccak256(&raw)))
+            }
+            "eth_getTransactionReceipt" => match polls.fetch_add(1, Ordering::SeqCst) {
+                0 => Ok(Value::Null),
+                _ => Ok(json!({
+                    "transactionHash": params[0],
+                    "blockNumber": "0x10",
+                    "gasUsed": "0x7530",
+                    "status": status,
+                })),
+            },
+            "eth_call" => replay.clone(),
+            other => panic!("unexpected method {other}"),
+        }
+    }
+
+    fn store_for(node: &MockRpc) -> DataStore {
+        let signer = Signer::from_hex("0x4646464646464646464646464646464646464646464646464646464646464646").unwrap();
+        let sender = TxSender::new(signer, Duration::from_secs(5), Duration::from_millis(10));
+        DataStore::new(STORE, RpcClient::new(reqwest::Client::new(), node.url()), Some(sender))
+    }
+
+    #[tokio::test]
+    async fn update_vault_growth_submits_signed_transaction() {
+        let node = MockRpc::start(oasis_node(Ok(json!("0x7530")), "0x1", Ok(json!("0x")))).await;
+        let store = store_for(&node);
+
+        let receipt = store.update_vault_growth(CHILD, U256::from(42u64)).await.unwrap();
+        assert!(receipt.succeeded());
+
+        let calls = node.calls();
+        let (_, estimate) = calls.iter().find(|(m, _)| m == "eth_estimateGas").unwrap();
+        let expected = ChildDataStore::updateVaultGrowthCall {
+            _childAddress: CHILD,
+            _newGrowth: U256::from(42u64),
+        }
+        .abi_encode();
+        assert_eq!(estimate[0]["data"], json!(hex::encode_prefixed(&expected)));
+        assert_eq!(estimate[0]["to"].as_str().unwrap().parse::<Address>().unwrap(), STORE);
+
+        let (_, raw) = calls.iter().find(|(m, _)| m == "eth_sendRawTransaction").unwrap();
+        let raw: Bytes = raw[0].as_str().unwrap().parse().unwrap();
+        assert_eq!(receipt.transaction_hash, keccak256(&raw));
+        assert!(raw.windows(expected.len()).any(|w| w == expected.as_slice()));
+    }
+
+    #[tokio::test]
+    async fn simulated_revert_decodes_profile_not_found() {
+        let node = MockRpc::start(oasis_node(
+            Err(reverted(ChildDataStore::ProfileNotFound::SELECTOR)),
+            "0x1",
+            Ok(json!("0x")),
+        ))
+        .await;
+        let store = store_for(&node);
+
+        let err = store.update_vault_growth(CHILD, U256::from(1u64)).await.unwrap_err();
+        assert!(matches!(err, Error::Revert { error: Some("ProfileNotFound"), .. }), "{err}");
+        assert!(node.calls().iter().all(|(m, _)| m != "eth_sendRawTransaction"));
+    }
+
+    #[tokio::test]
+    async fn mined_revert_is_replayed_and_decodes_unauthorized() {
+        let node = MockRpc::start(oasis_node(
+            Ok(json!("0x7530")),
+            "0x0",
+            Err(reverted(ChildDataStore::Unauthorized::SELECTOR)),
+        ))
+        .await;
+        let store = store_for(&node);
+
+        let err = store.update_vault_growth(CHILD, U256::from(1u64)).await.unwrap_err();
+        assert!(matches!(err, Error::Revert { error: Some("Unauthorized"), .. }), "{err}");
+
+        let (_, replay) = node.calls().into_iter().find(|(m, _)| m == "eth_call").unwrap();
+        assert_eq!(replay[1], json!("0xf"), "replayed on the parent block");
+    }
+
+    #[tokio::test]
+    async fn unknown_revert_is_passed_through() {
+        let node = MockRpc::start(oasis_node(Err(reverted([0xde, 0xad, 0xbe, 0xef])), "0x1", Ok(json!("0x")))).await;
+        let store = store_for(&node);
+
+        let err = store.update_vault_growth(CHILD, U256::from(1u64)).await.unwrap_err();
+        assert!(matches!(err, Error::Revert { error: None, .. }), "{err}");
+    }
+
+    #[tokio::test]
+    async fn age_verified_does_not_need_an_authenticated_caller() {
+        // Like Sapphire: an unsigned call runs from the zero address whatever
+        // `from` says, so only the access-free views answer
+        let node = MockRpc::start(|method, params| {
+            assert_eq!(method, "eth_call");
+            let data: Bytes = params[0]["data"].as_str().unwrap().parse().unwrap();
+            match ChildDataStore::isAgeVerifiedCall::abi_decode(&data) {
+                Ok(call) if params[0].get("from").is_none() => Ok(json!(Bytes::from(
+                    ChildDataStore::isAgeVerifiedCall::abi_encode_returns(&(call._childAddress == CHILD))
+                ))),
+                _ => Err(reverted(ChildDataStore::Unauthorized::SELECTOR)),
+            }
+        })
+        .await;
+        let store = store_for(&node);
+
+        assert!(store.age_verified(CHILD).await.unwrap());
+        assert!(!store.age_verified(STORE).await.unwrap());
+    }
+}
@@ -0,0 +1,163 @@
+use crate::config::MonitoringConfig;
+use crate::contracts::ChildDataStore::{self, ChildDataStoreErrors};
+use crate::contracts::{ScholarFiAgeVerifier, ScholarFiBridge, ScholarFiVault};
+use crate::error::{Error, Result};
+use crate::rpc::RpcClient;
+use alloy_primitives::Address;
+use alloy_sol_types::SolCall;
+use std::collections::BTreeSet;
+
+/// Check the configured contracts against what is deployed
+///
+/// Every configured address must hold code, and the contracts must point
+/// at each other the way the configuration says: the vault at the Celo
+/// mailbox and the bridge, the bridge at the Base mailbox, Celo's domain
+/// and the vault, and the vault and age verifier at the same Self
+/// verification config. ChildDataStore must have the views the monitor
+/// reads without a signer. Returns one message per problem, so the caller
+/// can list them all at once; contracts without code are not called.
+pub async fn verify_deployment(
+    config: &MonitoringConfig,
+    celo: &RpcClient,
+    oasis: &RpcClient,
+    base: &RpcClient,
+) -> Vec<String> {
+    let profile = config.network.profile();
+    let (celo_name, oasis_name, base_name) = (profile.celo.name, profile.oasis.name, profile.base.name);
+    let contracts = [
+        ("ScholarFiVault", Some(config.scholar_fi_vault_address), celo_name, celo),
+        ("ScholarFiAgeVerifier", config.age_verifier_address, celo_name, celo),
+        ("Aave data provider", Some(config.aave_data_provider_address), celo_name, celo),
+        ("Aave asset", Some(config.aave_asset_address), celo_name, celo),
+        ("Multicall3", config.multicall_address, celo_name, celo),
+        ("Celo Mailbox", config.celo_mailbox_address, celo_name, celo),
+        ("ChildDataStore", Some(config.child_data_store_address), oasis_name, oasis),
+        ("ParentDepositSplitter", config.deposit_splitter_address, base_name, base),
+        ("ScholarFiBridge", config.bridge_address, base_name, base),
+        ("Base Mailbox", config.base_mailbox_address, base_name, base),
+    ];
+
+    let mut problems = Vec::new();
+    let mut deployed = BTreeSet::new();
+    for (name, address, chain, rpc) in contracts {
+        let Some(address) = address else {
+            continue;
+        };
+        match rpc.code(address).await {
+            Ok(code) if code.is_empty() => problems.push(format!("{} {} on {} has no code", name, address, chain)),
+            Ok(_) => {
+                deployed.insert(name);
+            }
+            Err(e) => problems.push(format!("could not read the code of {} {} on {}: {}", name, address, chain, e)),
+        }
+    }
+
+    let vault = config.scholar_fi_vault_address;
+    if deployed.contains("ScholarFiVault") {
+        match call(celo, vault, ScholarFiVault::getHyperlaneConfigCall {}).await {
+            Ok(hyperlane) => {
+                if let Some(mailbox) = config.celo_mailbox_address.filter(|&m| m != hyperlane.mailboxAddress) {
+                    problems.push(format!(
+                        "ScholarFiVault.getHyperlaneConfig() returns mailbox {}, but celo_mailbox_address is {}",
+                        hyperlane.mailboxAddress, mailbox
+                    ));
+                }
+                if let Some(bridge) = config.bridge_address.filter(|&b| b != hyperlane.authorizedBridge) {
+                    problems.push(format!(
+                        "ScholarFiVault.getHyperlaneConfig() returns authorized bridge {}, but bridge_address is {}",
+                        hyperlane.authorizedBridge, bridge
+                    ));
+                }
+            }
+            Err(e) => problems.push(format!("ScholarFiVault.getHyperlaneConfig() on {} failed: {}", vault, e)),
+        }
+    }
+
+    if let Some(bridge) = config.bridge_address.filter(|_| deployed.contains("ScholarFiBridge")) {
+        match call(base, bridge, ScholarFiBridge::getConfigCall {}).await {
+            Ok(bridged) => {
+                if let Some(mailbox) = config.base_mailbox_address.filter(|&m| m != bridged.mailbox) {
+                    problems.push(format!(
+                        "ScholarFiBridge.getConfig() returns mailbox {}, but base_mailbox_address is {}",
+                        bridged.mailbox, mailbox
+                    ));
+                }
+                if bridged.celoDomain != profile.celo.hyperlane_domain {
+                    problems.push(format!(
+                        "ScholarFiBridge.getConfig() returns Celo domain {}, but {} is domain {}",
+                        bridged.celoDomain, celo_name, profile.celo.hyperlane_domain
+                    ));
+                }
+                if bridged.celoVaultAddress != vault {
+                    problems.push(format!(
+                        "ScholarFiBridge.getConfig() returns vault {}, but scholar_fi_vault_address is {}",
+                        bridged.celoVaultAddress, vault
+                    ));
+                }
+            }
+            Err(e) => problems.push(format!("ScholarFiBridge.getConfig() on {} failed: {}", bridge, e)),
+        }
+    }
+
+    let store = config.child_data_store_address;
+    if deployed.contains("ChildDataStore") {
+        let views = [
+            (
+                "isAgeVerified(address)",
+                ChildDataStore::isAgeVerifiedCall { _childAddress: Address::ZERO }.abi_encode(),
+            ),
+            (
+                "getTotalDeposited(address)",
+                ChildDataStore::getTotalDepositedCall { _childAddress: Address::ZERO }.abi_encode(),
+            ),
+        ];
+        for (view, data) in views {
+            let result = oasis
+                .eth_call(store, data.into())
+                .await
+                .map_err(|e| e.name_revert(ChildDataStoreErrors::name_by_selector));
+            match result {
+                // The zero address has no profile, so a current store
+                // answers with ProfileNotFound
+                Ok(_) | Err(Error::Revert { error: Some(_), .. }) => {}
+                Err(Error::Revert { .. }) => problems.push(format!(
+                    "ChildDataStore {} on {} has no {}; redeploy it from contracts/oasis",
+                    store, oasis_name, view
+                )),
+                Err(e) => problems.push(format!("ChildDataStore.{} on {} failed: {}", view, store, e)),
+            }
+        }
+    }
+
+    let verifier = config
+        .age_verifier_address
+        .filter(|_| deployed.contains("ScholarFiVault") && deployed.contains("ScholarFiAgeVerifier"));
+    if let Some(verifier) = verifier {
+        let (vault_config, verifier_config) = tokio::join!(
+            call(celo, vault, ScholarFiVault::getVerificationConfigCall {}),
+            call(celo, verifier, ScholarFiAgeVerifier::getVerificationConfigCall {}),
+        );
+        match (vault_config, verifier_config) {
+            (Ok(ours), Ok(theirs)) if (ours.configId, ours.olderThan) != (theirs.configId, theirs.minAge) => {
+                problems.push(format!(
+                    "getVerificationConfig() disagrees: ScholarFiVault has config ID {} and minimum age {}, \
+                     ScholarFiAgeVerifier {} has config ID {} and minimum age {}",
+                    ours.configId, ours.olderThan, verifier, theirs.configId, theirs.minAge
+                ))
+            }
+            (Ok(_), Ok(_)) => {}
+            (Err(e), _) => problems.push(format!("ScholarFiVault.getVerificationConfig() on {} failed: {}", vault, e)),
+            (_, Err(e)) => problems.push(format!(
+                "ScholarFiAgeVerifier.getVerificationConfig() on {} failed: {}",
+                verifier, e
+            )),
+        }
+    }
+
+    problems
+}
+
+async fn call<C: SolCall>(rpc: &RpcClient, to: Address, call: C) -> Result<C::Return> {
+    let data = rpc.eth_call(to, call.abi_encode().into()).await?;
+    Ok(C::abi_decode_returns(&data)?)
+}
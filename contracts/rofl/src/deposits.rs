@@ -0,0 +1,236 @@
+use crate::contracts::{ParentDepositSplitter, ScholarFiVault};
+use crate::error::{Error, Result};
+use crate::rpc::{Log, RpcClient};
+use alloy_primitives::{Address, B256, U256};
+use alloy_sol_types::SolEvent;
+use log::{debug, info};
+use std::collections::BTreeMap;
+
+/// Contract a `FundsDeposited` event is read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepositSource {
+    /// ScholarFiVault on Celo
+    Vault,
+    /// ParentDepositSplitter on Base
+    Splitter,
+}
+
+impl DepositSource {
+    fn topic(self) -> B256 {
+        match self {
+            DepositSource::Vault => ScholarFiVault::FundsDeposited::SIGNATURE_HASH,
+            DepositSource::Splitter => ParentDepositSplitter::FundsDeposited::SIGNATURE_HASH,
+        }
+    }
+
+    /// Child and total amount of a deposit log
+    fn decode(self, log: &Log) -> Result<(Address, U256)> {
+        let topics = log.topics.iter().copied();
+        Ok(match self {
+            DepositSource::Vault => {
+                let event = ScholarFiVault::FundsDeposited::decode_raw_log(topics, &log.data)?;
+                (event.child, event.totalAmount)
+            }
+            DepositSource::Splitter => {
+                let event = ParentDepositSplitter::FundsDeposited::decode_raw_log(topics, &log.data)?;
+                (event.childAddress, event.totalAmount)
+            }
+        })
+    }
+}
+
+/// Identifies one deposit event across chains
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DepositKey {
+    pub chain: String,
+    pub tx_hash: B256,
+    pub log_index: u64,
+}
+
+/// A deposit seen on a source chain, and whether it reached Oasis
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deposit {
+    pub child: Address,
+    pub amount: U256,
+    pub block: u64,
+    /// The `recordDeposit` transaction, once one was broadcast. A deposit
+    /// with a transaction is not sent again unless that transaction is
+    /// found reverted or dropped.
+    pub recorded_tx: Option<B256>,
+}
+
+/// Indexes `FundsDeposited` logs of one contract
+///
+/// Only blocks at least `confirmations` deep are read: a deposit mirrored
+/// to Oasis cannot be taken back, so it must not come from a block that
+/// can still be reorganized away. Deposits are keyed by (chain, tx hash,
+/// log index) so that seeing a log twice never records it twice.
+pub struct DepositWatcher {
+    chain: &'static str,
+    contract: Address,
+    source: DepositSource,
+    next_block: u64,
+    page_size: u64,
+    confirmations: u64,
+}
+
+impl DepositWatcher {
+    pub fn new(
+        chain: &'static str,
+        contract: Address,
+        source: DepositSource,
+        start_block: u64,
+        page_size: u64,
+        confirmations: u64,
+    ) -> Self {
+        Self {
+            chain,
+            contract,
+            source,
+            next_block: start_block,
+            page_size: page_size.max(1),
+            confirmations,
+        }
+    }
+
+    /// Resume from a persisted checkpoint
+    pub fn restore(&mut self, next_block: u64) {
+        self.next_block = self.next_block.max(next_block);
+    }
+
+    /// First block not scanned yet
+    pub fn next_block(&self) -> u64 {
+        self.next_block
+    }
+
+    /// Add deposits from newly confirmed blocks to `deposits`, skipping
+    /// ones already there. Returns the number added.
+    pub async fn scan(&mut self, rpc: &RpcClient, deposits: &mut BTreeMap<DepositKey, Deposit>) -> Result<usize> {
+        let head = rpc.block_number().await?;
+        let Some(confirmed) = (head + 1).checked_sub(self.confirmations) else {
+            return Ok(0);
+        };
+        let mut found = 0;
+
+        while self.next_block <= confirmed {
+            let to_block = confirmed.min(self.next_block + self.page_size - 1);
+            debug!("Indexing {} deposits in blocks {}..={}", self.chain, self.next_block, to_block);
+
+            let logs = rpc
+                .get_logs(self.contract, &[self.source.topic()], self.next_block, to_block)
+                .await?;
+
+            for log in logs {
+                let (Some(tx_hash), Some(log_index)) = (log.transaction_hash, log.log_index) else {
+                    return Err(Error::Decode(format!(
+                        "{} deposit log in block {} has no transaction hash or log index",
+                        self.chain, log.block_number
+                    )));
+                };
+                let (child, amount) = self.source.decode(&log)?;
+                let key = DepositKey {
+                    chain: self.chain.to_string(),
+                    tx_hash,
+                    log_index: log_index.to(),
+                };
+                if deposits.contains_key(&key) {
+                    continue;
+                }
+                info!("{} deposit of {} wei for {} in tx {}", self.chain, amount, child, tx_hash);
+                deposits.insert(
+                    key,
+                    Deposit {
+                        child,
+                        amount,
+                        block: log.block_number.to(),
+                        recorded_tx: None,
+                    },
+                );
+                found += 1;
+            }
+
+            self.next_block = to_block + 1;
+        }
+
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc::MockChain;
+    use alloy_primitives::address;
+    use serde_json::{json, Value};
+
+    const VAULT: Address = address!("00000000000000000000000000000000000000aa");
+    const SPLITTER: Address = address!("00000000000000000000000000000000000000dd");
+    const PARENT: Address = address!("00000000000000000000000000000000000000bb");
+    const CHILD: Address = address!("0000000000000000000000000000000000000001");
+
+    fn splitter_log(amount: u64, tx: u8) -> Value {
+        let data = ParentDepositSplitter::FundsDeposited {
+            childAddress: CHILD,
+            parentWallet: PARENT,
+            totalAmount: U256::from(amount),
+            vaultAmount: U256::from(amount * 3 / 10),
+            checkingAmount: U256::from(amount - amount * 3 / 10),
+            timestamp: U256::from(1_700_000_000u64),
+        }
+        .encode_log_data();
+        json!({
+            "address": SPLITTER,
+            "topics": data.topics(),
+            "data": data.data,
+            "transactionHash": B256::repeat_byte(tx),
+            "logIndex": "0x1",
+        })
+    }
+
+    #[tokio::test]
+    async fn scans_confirmed_blocks_once() {
+        let chain = MockChain::start(20).await;
+        chain.add_log(4, splitter_log(1_000, 0x01));
+        chain.add_log(18, splitter_log(2_000, 0x02));
+        // Vault deposits are filtered out by topic
+        let vault = ScholarFiVault::FundsDeposited {
+            child: CHILD,
+            parent: PARENT,
+            totalAmount: U256::from(5u64),
+            vaultAmount: U256::from(1u64),
+            spendingAmount: U256::from(4u64),
+        }
+        .encode_log_data();
+        chain.add_log(5, json!({ "address": VAULT, "topics": vault.topics(), "data": vault.data }));
+        let rpc = RpcClient::new(reqwest::Client::new(), chain.url());
+        let mut watcher = DepositWatcher::new("base", SPLITTER, DepositSource::Splitter, 0, 8, 5);
+        let mut deposits = BTreeMap::new();
+
+        // Blocks 17..=20 are not confirmed yet
+        assert_eq!(watcher.scan(&rpc, &mut deposits).await.unwrap(), 1);
+        assert_eq!(watcher.next_block(), 17);
+        let key = DepositKey {
+            chain: "base".to_string(),
+            tx_hash: B256::repeat_byte(0x01),
+            log_index: 1,
+        };
+        assert_eq!(
+            deposits[&key],
+            Deposit {
+                child: CHILD,
+                amount: U256::from(1_000u64),
+                block: 4,
+                recorded_tx: None,
+            }
+        );
+
+        // Rescanning from scratch once block 18 is confirmed adds only the
+        // new deposit and keeps what is known about the first
+        deposits.get_mut(&key).unwrap().recorded_tx = Some(B256::repeat_byte(0xee));
+        let mut rescan = DepositWatcher::new("base", SPLITTER, DepositSource::Splitter, 0, 8, 5);
+        chain.reorg(21, 22);
+        assert_eq!(rescan.scan(&rpc, &mut deposits).await.unwrap(), 1);
+        assert_eq!(deposits.len(), 2);
+        assert_eq!(deposits[&key].recorded_tx, Some(B256::repeat_byte(0xee)));
+    }
+}
@@ -0,0 +1,270 @@
+use crate::rpc::JsonRpcError;
+use alloy_primitives::{Bytes, B256};
+use std::fmt;
+use std::time::Duration;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Everything that can go wrong in the monitor
+///
+/// Variants are grouped by where the failure happened rather than by call
+/// site, so the monitoring loop can decide what to do from `recovery()`
+/// alone: back off and retry, skip the item at hand, or stop.
+#[derive(Debug)]
+pub enum Error {
+    /// Could not reach the node or read its HTTP response
+    Transport(reqwest::Error),
+    /// The node answered with a JSON-RPC error object
+    Rpc(JsonRpcError),
+    /// The event subscription socket failed or was closed
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+    /// HTTP 429; `retry_after` is the server's `Retry-After`, if it sent one
+    RateLimited { retry_after: Option<Duration> },
+    /// Fewer RPC endpoints than required agreed on a quorum read
+    NoQuorum { agreeing: usize, required: usize },
+    /// A response, return value or stored record had an unexpected shape
+    Decode(String),
+    /// A call or transaction reverted. `error` is the custom error name
+    /// when the revert data matches the contract's ABI.
+    Revert { error: Option<&'static str>, data: Bytes },
+    /// Mined with status 0, and replaying it did not reproduce a revert
+    TxFailed(B256),
+    /// No receipt before the confirmation timeout
+    TxTimeout(B256),
+    /// The chain reorganized below the confirmation depth, so indexed
+    /// state can no longer be trusted
+    DeepReorg { depth: u64 },
+    /// A value did not fit the arithmetic it was fed into
+    Overflow(String),
+    /// Invalid or inconsistent configuration
+    Config(String),
+    /// Missing, malformed or unusable signing key
+    Signer(String),
+    /// The local state store failed
+    State(rusqlite::Error),
+}
+
+/// What the monitoring loop should do about an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// Transient: back off and run the cycle again
+    Retry,
+    /// Specific to one item (a child, a transaction): skip it and carry on
+    Skip,
+    /// Misconfiguration or broken invariant: retrying cannot help, stop
+    Abort,
+}
+
+/// JSON-RPC error codes that mean "try again later": limit exceeded,
+/// resource unavailable, internal error, and the 429 some providers use
+/// for rate limiting
+const RETRYABLE_RPC_CODES: &[i64] = &[-32005, -32002, -32603, 429];
+
+/// Request-level JSON-RPC errors: invalid request, method not found,
+/// invalid params. The node will keep rejecting the same call.
+const FATAL_RPC_CODES: &[i64] = &[-32600, -32601, -32602];
+
+impl Error {
+    pub fn recovery(&self) -> Recovery {
+        match self {
+            // Lagging or briefly forked endpoints usually converge
+            Error::Transport(_)
+            | Error::WebSocket(_)
+            | Error::RateLimited { .. }
+            | Error::NoQuorum { .. }
+            | Error::TxTimeout(_) => Recovery::Retry,
+            Error::Rpc(e) if RETRYABLE_RPC_CODES.contains(&e.code) => Recovery::Retry,
+            Error::Rpc(e) if FATAL_RPC_CODES.contains(&e.code) => Recovery::Abort,
+            // Generic server errors (-32000 and vendor codes) are mostly
+            // node hiccups such as a missing trie node behind a load balancer
+            Error::Rpc(_) => Recovery::Retry,
+            Error::Revert { .. } | Error::TxFailed(_) | Error::Overflow(_) => Recovery::Skip,
+            // Garbage return data almost always means the configured address
+            // is not the contract we think it is
+            Error::Decode(_) | Error::Config(_) | Error::Signer(_) | Error::State(_) => Recovery::Abort,
+            Error::DeepReorg { .. } => Recovery::Abort,
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        self.recovery() == Recovery::Retry
+    }
+
+    /// How long the server asked us to wait before trying again
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Error::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Name an undecoded revert with `names`, usually a sol! error enum's
+    /// `name_by_selector`. Other errors pass through untouched.
+    pub fn name_revert(self, names: fn([u8; 4]) -> Option<&'static str>) -> Self {
+        match self {
+            Error::Revert { error: None, data } if data.len() >= 4 => {
+                let selector = [data[0], data[1], data[2], data[3]];
+                Error::Revert {
+                    error: names(selector),
+                    data,
+                }
+            }
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Transport(e) => write!(f, "transport error: {}", e),
+            Error::Rpc(e) => write!(f, "{}", e),
+            Error::WebSocket(e) => write!(f, "websocket error: {}", e),
+            Error::RateLimited { retry_after: Some(wait) } => {
+                write!(f, "rate limited (retry after {}s)", wait.as_secs())
+            }
+            Error::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            Error::NoQuorum { agreeing, required } => {
+                write!(f, "no RPC quorum: {} of {} required endpoints agree", agreeing, required)
+            }
+            Error::Decode(msg) => write!(f, "decode error: {}", msg),
+            Error::Revert { error: Some(name), .. } => write!(f, "execution reverted: {}", name),
+            Error::Revert { error: None, data } if data.is_empty() => write!(f, "execution reverted"),
+            Error::Revert { error: None, data } => write!(f, "execution reverted ({})", data),
+            Error::TxFailed(hash) => write!(f, "transaction {} failed", hash),
+            Error::TxTimeout(hash) => write!(f, "timed out waiting for receipt of {}", hash),
+            Error::DeepReorg { depth } => {
+                write!(f, "chain reorganized deeper than the {}-block confirmation depth", depth)
+            }
+            Error::Overflow(msg) => write!(f, "out of range: {}", msg),
+            Error::Config(msg) => write!(f, "configuration error: {}", msg),
+            Error::Signer(msg) => write!(f, "signer error: {}", msg),
+            Error::State(e) => write!(f, "state store error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Transport(e) => Some(e),
+            Error::Rpc(e) => Some(e),
+            Error::WebSocket(e) => Some(e.as_ref()),
+            Error::State(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        // A body that is not valid JSON-RPC is the node's fault, not the network's
+        if e.is_decode() {
+            return Error::Decode(e.to_string());
+        }
+        Error::Transport(e)
+    }
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        Error::WebSocket(Box::new(e))
+    }
+}
+
+impl From<JsonRpcError> for Error {
+    /// Reverts come back as JSON-RPC errors; lift them out so they can be
+    /// decoded and skipped rather than retried
+    fn from(e: JsonRpcError) -> Self {
+        if e.code == 3 || e.message.contains("revert") {
+            return Error::Revert {
+                error: None,
+                data: e.revert_data().unwrap_or_default(),
+            };
+        }
+        Error::Rpc(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Decode(e.to_string())
+    }
+}
+
+impl From<alloy_sol_types::Error> for Error {
+    fn from(e: alloy_sol_types::Error) -> Self {
+        Error::Decode(e.to_string())
+    }
+}
+
+impl From<alloy_primitives::hex::FromHexError> for Error {
+    fn from(e: alloy_primitives::hex::FromHexError) -> Self {
+        Error::Decode(e.to_string())
+    }
+}
+
+impl From<std::num::ParseIntError> for Error {
+    fn from(e: std::num::ParseIntError) -> Self {
+        Error::Decode(e.to_string())
+    }
+}
+
+impl<T> From<alloy_primitives::ruint::FromUintError<T>> for Error {
+    fn from(e: alloy_primitives::ruint::FromUintError<T>) -> Self {
+        Error::Overflow(e.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::State(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rpc_error(code: i64, message: &str) -> Error {
+        JsonRpcError {
+            code,
+            message: message.to_string(),
+            data: None,
+        }
+        .into()
+    }
+
+    #[test]
+    fn classifies_rpc_errors_by_code() {
+        assert_eq!(rpc_error(-32005, "limit exceeded").recovery(), Recovery::Retry);
+        assert_eq!(rpc_error(-32000, "header not found").recovery(), Recovery::Retry);
+        assert_eq!(rpc_error(-32601, "the method eth_foo does not exist").recovery(), Recovery::Abort);
+        assert_eq!(rpc_error(-32000, "execution reverted").recovery(), Recovery::Skip);
+    }
+
+    #[test]
+    fn lifts_and_names_reverts() {
+        let err: Error = JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: Some(json!("0xdeadbeef")),
+        }
+        .into();
+        assert_eq!(err.to_string(), "execution reverted (0xdeadbeef)");
+
+        let named = err.name_revert(|selector| (selector == [0xde, 0xad, 0xbe, 0xef]).then_some("Unauthorized"));
+        assert!(matches!(named, Error::Revert { error: Some("Unauthorized"), .. }));
+        assert_eq!(named.to_string(), "execution reverted: Unauthorized");
+    }
+
+    #[test]
+    fn local_failures_are_fatal() {
+        assert_eq!(Error::Decode("empty return data".into()).recovery(), Recovery::Abort);
+        assert_eq!(Error::Config("bad address".into()).recovery(), Recovery::Abort);
+        assert_eq!(Error::Signer("bad key".into()).recovery(), Recovery::Abort);
+        assert_eq!(Error::TxTimeout(B256::ZERO).recovery(), Recovery::Retry);
+        assert_eq!(Error::TxFailed(B256::ZERO).recovery(), Recovery::Skip);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::contracts::ScholarFiBridge;
+use crate::error::{Error, Result};
+use crate::fixed_point::{Rounding, Wad};
+use crate::rpc::RpcClient;
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::SolCall;
+use serde::{Serialize, Serializer};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// What a parent has to send with `ScholarFiBridge.depositForChild`
+///
+/// The bridge keeps `msg.value` minus the Hyperlane fee it quotes at send
+/// time as the deposit, so the margin only guards against the fee rising
+/// between quote and send; whatever it does not consume is deposited too.
+/// Amounts are in wei and serialized as decimal strings.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct FeeQuote {
+    pub child: Address,
+    pub parent: Address,
+    /// Deposit the parent wants to make
+    #[serde(serialize_with = "decimal")]
+    pub amount: U256,
+    /// `ScholarFiBridge.estimateFees` for this child and parent
+    #[serde(serialize_with = "decimal")]
+    pub hyperlane_fee: U256,
+    pub margin_percent: u64,
+    #[serde(serialize_with = "decimal")]
+    pub margin: U256,
+    /// `msg.value` to send: amount, fee and margin
+    #[serde(serialize_with = "decimal")]
+    pub total: U256,
+    /// Unix time the fee was read from Base
+    pub quoted_at: u64,
+}
+
+/// Quotes bridge fees, caching each child and parent's fee for a short TTL
+///
+/// The fee only depends on the message body, `(child, parent)`, so quotes
+/// for different amounts share a cache entry.
+pub struct FeeQuoter {
+    bridge: Address,
+    base: RpcClient,
+    margin_percent: u64,
+    ttl: Duration,
+    cache: Mutex<HashMap<(Address, Address), CachedFee>>,
+}
+
+#[derive(Clone, Copy)]
+struct CachedFee {
+    fetched: Instant,
+    fee: U256,
+    quoted_at: u64,
+}
+
+impl FeeQuoter {
+    pub fn new(bridge: Address, base: RpcClient, margin_percent: u64, ttl: Duration) -> Self {
+        Self {
+            bridge,
+            base,
+            margin_percent,
+            ttl,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn quote(&self, child: Address, parent: Address, amount: U256, now: u64) -> Result<FeeQuote> {
+        let cached = self.cache.lock().unwrap().get(&(child, parent)).copied();
+        let (fee, quoted_at) = match cached {
+            Some(cached) if cached.fetched.elapsed() < self.ttl => (cached.fee, cached.quoted_at),
+            _ => {
+                // estimateFees reads the parent from msg.sender
+                let call = ScholarFiBridge::estimateFeesCall { childWallet: child, amount };
+                let data = self.base.eth_call_from(parent, self.bridge, call.abi_encode().into()).await?;
+                let fee = ScholarFiBridge::estimateFeesCall::abi_decode_returns(&data)?;
+                let mut cache = self.cache.lock().unwrap();
+                cache.retain(|_, cached| cached.fetched.elapsed() < self.ttl);
+                let fetched = Instant::now();
+                cache.insert((child, parent), CachedFee { fetched, fee, quoted_at: now });
+                (fee, now)
+            }
+        };
+
+        // Rounded up so the margin never comes out short
+        let margin = Wad::from_raw(fee)
+            .checked_mul(Wad::from_bps(self.margin_percent.saturating_mul(100)), Rounding::Up)
+            .ok_or_else(|| Error::Overflow("bridge fee margin".to_string()))?
+            .raw();
+        Ok(FeeQuote {
+            child,
+            parent,
+            amount,
+            hyperlane_fee: fee,
+            margin_percent: self.margin_percent,
+            margin,
+            total: amount.saturating_add(fee).saturating_add(margin),
+            quoted_at,
+        })
+    }
+}
+
+fn decimal<S: Serializer>(amount: &U256, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_str(amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc::MockRpc;
+    use alloy_primitives::{address, Bytes};
+    use alloy_sol_types::SolValue;
+    use serde_json::json;
+
+    const BRIDGE: Address = address!("00000000000000000000000000000000000000b1");
+    const CHILD: Address = address!("0000000000000000000000000000000000000001");
+    const PARENT: Address = address!("0000000000000000000000000000000000000002");
+
+    #[tokio::test]
+    async fn adds_margin_and_caches_per_child_and_parent() {
+        let node = MockRpc::start(|method, params| {
+            assert_eq!(method, "eth_call");
+            assert_eq!(params[0]["to"], json!(BRIDGE));
+            let data: Bytes = serde_json::from_value(params[0]["data"].clone()).unwrap();
+            ScholarFiBridge::estimateFeesCall::abi_decode(&data).unwrap();
+            // A fee per parent shows which cache entry answered
+            let fee = if params[0]["from"] == json!(PARENT) { 1_001u64 } else { 2_000 };
+            Ok(json!(Bytes::from(U256::from(fee).abi_encode())))
+        })
+        .await;
+        let quoter = FeeQuoter::new(
+            BRIDGE,
+            RpcClient::new(reqwest::Client::new(), node.url()),
+            10,
+            Duration::from_secs(60),
+        );
+
+        let quote = quoter.quote(CHILD, PARENT, U256::from(50_000u64), 1_700_000_000).await.unwrap();
+        assert_eq!(quote.hyperlane_fee, U256::from(1_001u64));
+        // 10% of 1001, rounded up
+        assert_eq!(quote.margin, U256::from(101u64));
+        assert_eq!(quote.total, U256::from(51_102u64));
+        let value = serde_json::to_value(&quote).unwrap();
+        assert_eq!(value["total"], json!("51102"));
+        assert_eq!(value["margin_percent"], json!(10));
+
+        // Another amount reuses the cached fee, another parent does not
+        let again = quoter.quote(CHILD, PARENT, U256::from(7u64), 1_700_000_010).await.unwrap();
+        assert_eq!((again.total, again.quoted_at), (U256::from(1_109u64), 1_700_000_000));
+        let other = quoter.quote(CHILD, CHILD, U256::from(7u64), 1_700_000_010).await.unwrap();
+        assert_eq!(other.hyperlane_fee, U256::from(2_000u64));
+        assert_eq!(node.calls().len(), 2);
+    }
+}
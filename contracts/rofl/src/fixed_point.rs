@@ -0,0 +1,324 @@
+//! Fixed-point ray/wad arithmetic
+//!
+//! Aave-style decimal fixed point: a `Wad` has 18 decimals (the precision of
+//! wei balances) and a `Ray` has 27 (the precision of Aave rates). Values
+//! are unsigned 256-bit integers and every product or quotient goes through
+//! a 512-bit intermediate, so nothing is lost to `f64`.
+//!
+//! All arithmetic is checked and returns `None` on overflow or division by
+//! zero. Anything that can lose precision takes an explicit `Rounding`.
+
+use alloy_primitives::{U256, U512};
+use std::fmt;
+
+/// How to round a result that is not exactly representable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Towards zero
+    Down,
+    /// Away from zero
+    Up,
+    /// To nearest, ties away from zero (Aave's `rayMul`/`wadMul`)
+    HalfUp,
+}
+
+/// `a * b / denominator` with a 512-bit intermediate product
+pub fn mul_div(a: U256, b: U256, denominator: U256, rounding: Rounding) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+
+    let product: U512 = a.widening_mul(b);
+    let denominator = U512::from(denominator);
+    let (mut quotient, remainder) = product.div_rem(denominator);
+
+    let round_up = match rounding {
+        Rounding::Down => false,
+        Rounding::Up => !remainder.is_zero(),
+        Rounding::HalfUp => remainder >= denominator - remainder,
+    };
+    if round_up {
+        quotient += U512::from(1u8);
+    }
+
+    if quotient.bit_len() > 256 {
+        return None;
+    }
+    let limbs = quotient.as_limbs();
+    Some(U256::from_limbs([limbs[0], limbs[1], limbs[2], limbs[3]]))
+}
+
+macro_rules! fixed_point {
+    ($name:ident, $decimals:expr) => {
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(U256);
+
+        impl $name {
+            pub const DECIMALS: usize = $decimals;
+            pub const ONE: Self = Self(U256::from_limbs(pow10_limbs($decimals)));
+
+            /// Wrap a raw scaled integer (`1.0` is `10^DECIMALS`)
+            pub const fn from_raw(raw: U256) -> Self {
+                Self(raw)
+            }
+
+            #[allow(dead_code, reason = "the monitor only unwraps wads; rays are unwrapped in tests")]
+            pub const fn raw(self) -> U256 {
+                self.0
+            }
+
+            /// `n / 10_000`, e.g. `from_bps(350)` is 3.5%
+            pub fn from_bps(bps: u64) -> Self {
+                Self(Self::ONE.0 * U256::from(bps) / U256::from(10_000u64))
+            }
+
+            pub fn checked_mul(self, rhs: Self, rounding: Rounding) -> Option<Self> {
+                mul_div(self.0, rhs.0, Self::ONE.0, rounding).map(Self)
+            }
+
+            #[allow(dead_code, reason = "the monitor only divides rays")]
+            pub fn checked_div(self, rhs: Self, rounding: Rounding) -> Option<Self> {
+                mul_div(self.0, Self::ONE.0, rhs.0, rounding).map(Self)
+            }
+        }
+
+        impl fmt::Display for $name {
+            /// Decimal with trailing zeros trimmed, e.g. `0.035`
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let (int, frac) = self.0.div_rem(Self::ONE.0);
+                if frac.is_zero() {
+                    return write!(f, "{}", int);
+                }
+                let frac = format!("{:0>width$}", frac.to_string(), width = Self::DECIMALS);
+                write!(f, "{}.{}", int, frac.trim_end_matches('0'))
+            }
+        }
+    };
+}
+
+/// Limbs of `10^exp` for the `ONE` constants
+const fn pow10_limbs(exp: usize) -> [u64; 4] {
+    let mut lo: u128 = 1;
+    let mut i = 0;
+    while i < exp {
+        lo *= 10;
+        i += 1;
+    }
+    [lo as u64, (lo >> 64) as u64, 0, 0]
+}
+
+fixed_point!(Wad, 18);
+fixed_point!(Ray, 27);
+
+const WAD_RAY_RATIO: u64 = 1_000_000_000;
+
+impl Wad {
+    pub fn to_ray(self) -> Option<Ray> {
+        self.0.checked_mul(U256::from(WAD_RAY_RATIO)).map(Ray)
+    }
+}
+
+impl Ray {
+    pub const ZERO: Self = Self(U256::ZERO);
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.0.checked_add(rhs.0).map(Self)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.0.checked_sub(rhs.0).map(Self)
+    }
+
+    /// Multiply by a plain integer, e.g. a fraction by 100 for a percentage
+    pub fn checked_mul_int(self, n: U256) -> Option<Self> {
+        self.0.checked_mul(n).map(Self)
+    }
+
+    /// Divide by a plain integer, e.g. an annual rate by seconds per year
+    pub fn checked_div_int(self, n: U256, rounding: Rounding) -> Option<Self> {
+        mul_div(self.0, U256::from(1u8), n, rounding).map(Self)
+    }
+
+    /// `self^exponent` by binary exponentiation, rounding every step
+    pub fn checked_pow(self, mut exponent: u64, rounding: Rounding) -> Option<Self> {
+        let mut base = self;
+        let mut result = Self::ONE;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.checked_mul(base, rounding)?;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.checked_mul(base, rounding)?;
+            }
+        }
+        Some(result)
+    }
+
+    /// Lossy float, for reporting only (metrics, logs)
+    pub fn to_f64(self) -> f64 {
+        f64::from(self.0) / f64::from(Self::ONE.0)
+    }
+
+    pub fn to_wad(self, rounding: Rounding) -> Wad {
+        // Dividing by 1e9 cannot overflow
+        Wad(mul_div(self.0, U256::from(1u8), U256::from(WAD_RAY_RATIO), rounding).unwrap_or_default())
+    }
+
+    /// The factor `(1 + self)^seconds` for a per-second rate
+    pub fn compound(self, seconds: u64) -> Option<Ray> {
+        Ray::ONE.checked_add(self)?.checked_pow(seconds, Rounding::HalfUp)
+    }
+
+    /// Annual yield from an annual rate compounded every second,
+    /// `(1 + self / periods)^periods - 1`
+    pub fn compounded_yield(self, periods: u64) -> Option<Ray> {
+        self.checked_div_int(U256::from(periods), Rounding::Down)?
+            .compound(periods)?
+            .checked_sub(Ray::ONE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigInt;
+    use num_rational::BigRational;
+    use num_traits::{One, Signed};
+    use proptest::prelude::*;
+
+    fn big(x: U256) -> BigInt {
+        BigInt::parse_bytes(x.to_string().as_bytes(), 10).unwrap()
+    }
+
+    fn ratio(x: U256) -> BigRational {
+        BigRational::from_integer(big(x))
+    }
+
+    fn round(value: &BigRational, rounding: Rounding) -> BigInt {
+        match rounding {
+            Rounding::Down => value.floor().to_integer(),
+            Rounding::Up => value.ceil().to_integer(),
+            Rounding::HalfUp => {
+                let half = BigRational::new(1.into(), 2.into());
+                (value + half).floor().to_integer()
+            }
+        }
+    }
+
+    fn rounding() -> impl Strategy<Value = Rounding> {
+        prop_oneof![Just(Rounding::Down), Just(Rounding::Up), Just(Rounding::HalfUp)]
+    }
+
+    fn u256() -> impl Strategy<Value = U256> {
+        prop_oneof![
+            any::<u64>().prop_map(U256::from),
+            any::<u128>().prop_map(U256::from),
+            any::<[u64; 4]>().prop_map(U256::from_limbs),
+        ]
+    }
+
+    /// `(1 + r)^n` as an exact rational, truncated to `10^-60` after each
+    /// squaring so the reference stays small enough to compute
+    fn reference_compound(rate: Ray, mut n: u64) -> BigRational {
+        let scale = BigInt::from(10u8).pow(60);
+        let truncate = |x: BigRational| BigRational::new((x * &scale).floor().to_integer(), scale.clone());
+        let mut base = BigRational::one() + ratio(rate.raw()) / ratio(Ray::ONE.raw());
+        let mut result = BigRational::one();
+        while n > 0 {
+            if n & 1 == 1 {
+                result = truncate(&result * &base);
+            }
+            base = truncate(&base * &base);
+            n >>= 1;
+        }
+        result
+    }
+
+    proptest! {
+        #[test]
+        fn mul_div_matches_rational(a in u256(), b in u256(), d in u256(), r in rounding()) {
+            let expected = if d.is_zero() {
+                None
+            } else {
+                Some(round(&(ratio(a) * ratio(b) / ratio(d)), r))
+            };
+            let expected = expected.filter(|q| q.bits() <= 256);
+            prop_assert_eq!(mul_div(a, b, d, r).map(big), expected);
+        }
+
+        #[test]
+        fn ray_mul_and_div_match_rational(a in any::<u128>(), b in any::<u128>(), r in rounding()) {
+            let (x, y) = (Ray::from_raw(U256::from(a)), Ray::from_raw(U256::from(b)));
+            let one = ratio(Ray::ONE.raw());
+
+            let product = round(&(ratio(x.raw()) * ratio(y.raw()) / &one), r);
+            prop_assert_eq!(x.checked_mul(y, r).map(|v| big(v.raw())), Some(product));
+
+            let quotient = (!y.raw().is_zero()).then(|| round(&(ratio(x.raw()) * &one / ratio(y.raw())), r));
+            prop_assert_eq!(x.checked_div(y, r).map(|v| big(v.raw())), quotient);
+        }
+
+        #[test]
+        fn wad_mul_and_div_match_rational(a in any::<u128>(), b in any::<u128>(), r in rounding()) {
+            let (x, y) = (Wad::from_raw(U256::from(a)), Wad::from_raw(U256::from(b)));
+            let one = ratio(Wad::ONE.raw());
+
+            let product = round(&(ratio(x.raw()) * ratio(y.raw()) / &one), r);
+            prop_assert_eq!(x.checked_mul(y, r).map(|v| big(v.raw())), Some(product));
+
+            let quotient = (!y.raw().is_zero()).then(|| round(&(ratio(x.raw()) * &one / ratio(y.raw())), r));
+            prop_assert_eq!(x.checked_div(y, r).map(|v| big(v.raw())), quotient);
+        }
+
+        #[test]
+        fn wad_ray_round_trip(a in any::<u128>(), r in rounding()) {
+            let wad = Wad::from_raw(U256::from(a));
+            prop_assert_eq!(wad.to_ray().unwrap().to_wad(r), wad);
+
+            let ray = Ray::from_raw(U256::from(a));
+            let expected = round(&(ratio(ray.raw()) / BigRational::from_integer(WAD_RAY_RATIO.into())), r);
+            prop_assert_eq!(big(ray.to_wad(r).raw()), expected);
+        }
+
+        #[test]
+        fn compound_tracks_rational_reference(bps in 0u64..5_000, seconds in 0u64..=365 * 24 * 3600) {
+            // Per-second rate for an annual rate of up to 50%
+            let rate = Ray::from_bps(bps).checked_div_int(U256::from(365u64 * 24 * 3600), Rounding::Down).unwrap();
+            let actual = ratio(rate.compound(seconds).unwrap().raw());
+            let expected = reference_compound(rate, seconds) * ratio(Ray::ONE.raw());
+
+            // Each squaring doubles the relative error carried in, so the
+            // worst case grows linearly with the exponent (~1e-20 relative
+            // over a year, far below a wei on any real balance)
+            let error = (actual - expected).abs();
+            let bound = BigRational::from_integer((2 * seconds + 2).into());
+            prop_assert!(error <= bound, "error {} ulp over {}s", error, seconds);
+        }
+    }
+
+    #[test]
+    fn constants_and_display() {
+        assert_eq!(Wad::ONE.raw(), U256::from(10u64).pow(U256::from(18u64)));
+        assert_eq!(Ray::ONE.raw(), U256::from(10u64).pow(U256::from(27u64)));
+        assert_eq!(Ray::from_bps(350).to_string(), "0.035");
+        assert_eq!(Wad::from_raw(Wad::ONE.raw() * U256::from(3u8)).to_string(), "3");
+        assert_eq!(Ray::ZERO.to_string(), "0");
+    }
+
+    #[test]
+    fn compounded_yield_exceeds_simple_rate() {
+        // 5% APR compounded every second is ~5.127% APY
+        let apy = Ray::from_bps(500).compounded_yield(365 * 24 * 3600).unwrap();
+        assert!(apy > Ray::from_bps(512) && apy < Ray::from_bps(513), "{}", apy);
+    }
+
+    #[test]
+    fn overflow_and_division_by_zero_are_none() {
+        let max = Ray::from_raw(U256::MAX);
+        assert_eq!(max.checked_mul(max, Rounding::Down), None);
+        assert_eq!(Ray::ONE.checked_div(Ray::ZERO, Rounding::Down), None);
+        assert_eq!(max.checked_add(Ray::ONE), None);
+        assert_eq!(Ray::ZERO.checked_sub(Ray::ONE), None);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::metrics::Metrics;
+use crate::rpc::RpcClient;
+use alloy_primitives::{Address, U256};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Result of one readiness probe, served as the `/readyz` JSON body
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct Readiness {
+    pub ready: bool,
+    /// Human-readable reason for every failed check
+    pub failures: Vec<String>,
+}
+
+/// Decides whether the monitor is doing its job
+///
+/// The monitor is ready when its last successful cycle is recent (within
+/// `max_cycle_age`), every configured RPC endpoint answers, and the signer
+/// can still pay for gas. All checks run on every probe so the response
+/// lists every problem, not just the first.
+pub struct ReadinessProbe {
+    metrics: Arc<Metrics>,
+    max_cycle_age: Duration,
+    endpoints: Vec<(&'static str, RpcClient)>,
+    /// Signer and the chain it pays gas on; `None` in dry-run mode
+    signer: Option<(Address, RpcClient)>,
+    min_signer_balance: U256,
+}
+
+impl ReadinessProbe {
+    pub fn new(metrics: Arc<Metrics>, max_cycle_age: Duration, min_signer_balance: U256) -> Self {
+        Self {
+            metrics,
+            max_cycle_age,
+            endpoints: Vec::new(),
+            signer: None,
+            min_signer_balance,
+        }
+    }
+
+    /// Require `rpc` to be reachable
+    pub fn endpoint(mut self, chain: &'static str, rpc: RpcClient) -> Self {
+        self.endpoints.push((chain, rpc));
+        self
+    }
+
+    /// Require `address` to hold at least the minimum balance on `rpc`
+    pub fn signer(mut self, address: Address, rpc: RpcClient) -> Self {
+        self.signer = Some((address, rpc));
+        self
+    }
+
+    pub async fn check(&self, now: u64) -> Readiness {
+        let mut failures = Vec::new();
+
+        let last_success = self.metrics.last_success.get().max(0) as u64;
+        if last_success == 0 {
+            failures.push("no successful monitoring cycle yet".to_string());
+        } else if now.saturating_sub(last_success) > self.max_cycle_age.as_secs() {
+            failures.push(format!(
+                "last successful cycle was {}s ago (limit {}s)",
+                now.saturating_sub(last_success),
+                self.max_cycle_age.as_secs()
+            ));
+        }
+
+        for (chain, rpc) in &self.endpoints {
+            if let Err(e) = rpc.block_number().await {
+                failures.push(format!("{} RPC {} unreachable: {}", chain, rpc.host(), e));
+            }
+        }
+
+        if let Some((address, rpc)) = &self.signer {
+            match rpc.balance(*address).await {
+                Ok(balance) if balance < self.min_signer_balance => failures.push(format!(
+                    "signer {} balance {} wei is below {} wei",
+                    address, balance, self.min_signer_balance
+                )),
+                Ok(_) => {}
+                Err(e) => failures.push(format!("signer {} balance unavailable: {}", address, e)),
+            }
+        }
+
+        Readiness {
+            ready: failures.is_empty(),
+            failures,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc::MockRpc;
+    use alloy_primitives::address;
+    use serde_json::json;
+
+    const SIGNER: Address = address!("00000000000000000000000000000000000000cc");
+    const NOW: u64 = 1_700_000_000;
+
+    fn rpc(url: &str) -> RpcClient {
+        RpcClient::new(reqwest::Client::new(), url)
+    }
+
+    async fn node(balance: u64) -> MockRpc {
+        MockRpc::start(move |method, _| match method {
+            "eth_blockNumber" => Ok(json!("0x10")),
+            "eth_getBalance" => Ok(json!(U256::from(balance))),
+            other => panic!("unexpected method {other}"),
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn ready_when_every_check_passes() {
+        let node = node(1_000).await;
+        let metrics = Arc::new(Metrics::new());
+        metrics.last_success.set((NOW - 100) as i64);
+
+        let probe = ReadinessProbe::new(metrics, Duration::from_secs(120), U256::from(500))
+            .endpoint("celo", rpc(node.url()))
+            .signer(SIGNER, rpc(node.url()));
+
+        assert_eq!(
+            probe.check(NOW).await,
+            Readiness {
+                ready: true,
+                failures: vec![]
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_every_failed_check() {
+        let node = node(100).await;
+        let metrics = Arc::new(Metrics::new());
+        metrics.last_success.set((NOW - 300) as i64);
+
+        let probe = ReadinessProbe::new(metrics, Duration::from_secs(120), U256::from(500))
+            .endpoint("celo", rpc(node.url()))
+            .endpoint("base", rpc("http://127.0.0.1:1"))
+            .signer(SIGNER, rpc(node.url()));
+
+        let readiness = probe.check(NOW).await;
+        assert!(!readiness.ready);
+        assert_eq!(readiness.failures.len(), 3, "{:?}", readiness.failures);
+        assert_eq!(readiness.failures[0], "last successful cycle was 300s ago (limit 120s)");
+        assert!(readiness.failures[1].starts_with("base RPC 127.0.0.1:1 unreachable"));
+        assert!(readiness.failures[2].contains("balance 100 wei is below 500 wei"));
+    }
+
+    #[tokio::test]
+    async fn not_ready_before_first_cycle() {
+        let probe = ReadinessProbe::new(Arc::new(Metrics::new()), Duration::from_secs(120), U256::ZERO);
+
+        let readiness = probe.check(NOW).await;
+        assert_eq!(readiness.failures, vec!["no successful monitoring cycle yet".to_string()]);
+    }
+}
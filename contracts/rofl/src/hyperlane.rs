@@ -0,0 +1,517 @@
+use crate::contracts::IMailbox::{Dispatch, Process, ProcessId};
+use crate::contracts::ScholarFiVault::{self, ScholarFiVaultErrors};
+use crate::error::{Error, Result};
+use crate::message::{DepositBody, HyperlaneMessage};
+use crate::rpc::{Log, RpcClient};
+use alloy_primitives::{Address, Bytes, B256};
+use alloy_sol_types::{SolCall, SolEvent};
+use log::{debug, info, warn};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Block and time a message was seen at on one side of the bridge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sighting {
+    pub block: u64,
+    pub timestamp: u64,
+}
+
+/// How far one ScholarFiBridge message has got
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Delivery {
+    /// Child the deposit is for, from the dispatched message body
+    pub child: Option<Address>,
+    /// The message as dispatched, to simulate its delivery with
+    pub message: Option<HyperlaneMessage>,
+    /// Dispatched through the Mailbox on Base
+    pub dispatched: Option<Sighting>,
+    /// Processed by the Mailbox on Celo, i.e. `ScholarFiVault.handle` ran
+    pub delivered: Option<Sighting>,
+    /// An alert was raised for it being overdue
+    pub alerted: bool,
+}
+
+impl Delivery {
+    /// Seconds from dispatch to delivery, once both sides were seen
+    pub fn latency(&self) -> Option<u64> {
+        Some(self.delivered?.timestamp.saturating_sub(self.dispatched?.timestamp))
+    }
+
+    /// Dispatched more than `timeout` seconds before `now` and not delivered
+    pub fn is_overdue(&self, now: u64, timeout: u64) -> bool {
+        self.delivered.is_none()
+            && self
+                .dispatched
+                .is_some_and(|dispatched| now.saturating_sub(dispatched.timestamp) > timeout)
+    }
+}
+
+/// Why `ScholarFiVault.handle` would reject a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandleRevert {
+    /// The message was not sent by the vault's authorized bridge
+    UnauthorizedSender,
+    /// The child has no account on the vault yet
+    AccountNotFound,
+    /// The child's account belongs to another parent
+    NotParent,
+    /// The delivery carried no value
+    ZeroAmount,
+    /// Any other revert, named if the vault declares the error
+    Other { error: Option<&'static str>, data: Bytes },
+}
+
+impl HandleRevert {
+    fn from_revert(error: Option<&'static str>, data: Bytes) -> Self {
+        match error {
+            Some("UnauthorizedSender") => HandleRevert::UnauthorizedSender,
+            Some("AccountNotFound") => HandleRevert::AccountNotFound,
+            Some("NotParent") => HandleRevert::NotParent,
+            Some("ZeroAmount") => HandleRevert::ZeroAmount,
+            error => HandleRevert::Other { error, data },
+        }
+    }
+
+    /// What has to happen for `message` to go through
+    pub fn remediation(&self, message: &HyperlaneMessage) -> String {
+        let body = DepositBody::decode(&message.body).ok();
+        let (child, parent) = match body {
+            Some(body) => (body.child.to_string(), body.parent.to_string()),
+            None => ("the child".to_string(), "the parent".to_string()),
+        };
+        match self {
+            HandleRevert::UnauthorizedSender => format!(
+                "the vault only accepts messages from its authorized bridge, not {}; \
+                 check ScholarFiVault.getHyperlaneConfig() and redeploy whichever side is stale, \
+                 this message can never be processed",
+                message.sender_address()
+            ),
+            HandleRevert::AccountNotFound => format!(
+                "parent {} must create the child account first with ScholarFiVault.createChildAccount({}) \
+                 on Celo, then the message can be processed",
+                parent, child
+            ),
+            HandleRevert::NotParent => format!(
+                "child {} is registered to another parent than {}, so this message can never be processed; \
+                 the deposit has to be refunded on Base",
+                child, parent
+            ),
+            HandleRevert::ZeroAmount => format!(
+                "relayers call Mailbox.process without value, so the deposit for {} never reaches the vault; \
+                 process message {} manually on Celo with Mailbox.process and the deposit amount attached",
+                child,
+                message.id()
+            ),
+            HandleRevert::Other { data, .. } => format!(
+                "no remediation is known for this revert (data {}); find it in ScholarFiVault.handle \
+                 or the Celo Mailbox",
+                data
+            ),
+        }
+    }
+}
+
+impl fmt::Display for HandleRevert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandleRevert::UnauthorizedSender => write!(f, "UnauthorizedSender"),
+            HandleRevert::AccountNotFound => write!(f, "AccountNotFound"),
+            HandleRevert::NotParent => write!(f, "NotParent"),
+            HandleRevert::ZeroAmount => write!(f, "ZeroAmount"),
+            HandleRevert::Other { error: Some(name), .. } => write!(f, "{}", name),
+            HandleRevert::Other { error: None, data } if data.is_empty() => write!(f, "an empty revert"),
+            HandleRevert::Other { error: None, data } => write!(f, "unknown error {}", data),
+        }
+    }
+}
+
+/// One Mailbox and the next block to read from it
+pub struct Mailbox {
+    pub address: Address,
+    pub next_block: u64,
+}
+
+/// Follows ScholarFiBridge deposits from Base to ScholarFiVault on Celo
+///
+/// The Base Mailbox emits `Dispatch` with the full message for every
+/// message the bridge sends, and its ID is rebuilt from that. The Celo
+/// Mailbox emits `Process` (naming the recipient) followed by `ProcessId`
+/// in the same transaction when it delivers one. Both sides only read
+/// confirmed blocks, and a delivery is recorded even before its dispatch
+/// was seen, since Celo can confirm first.
+pub struct DeliveryTracker {
+    bridge: Address,
+    vault: Address,
+    origin: Mailbox,
+    destination: Mailbox,
+    page_size: u64,
+    confirmations: u64,
+}
+
+/// Which side of the bridge a scan reads
+#[derive(Clone, Copy)]
+enum Side {
+    Dispatch,
+    Delivery,
+}
+
+impl DeliveryTracker {
+    pub fn new(
+        bridge: Address,
+        vault: Address,
+        origin: Mailbox,
+        destination: Mailbox,
+        page_size: u64,
+        confirmations: u64,
+    ) -> Self {
+        Self {
+            bridge,
+            vault,
+            origin,
+            destination,
+            page_size: page_size.max(1),
+            confirmations,
+        }
+    }
+
+    /// Resume from persisted checkpoints
+    pub fn restore(&mut self, dispatch_block: u64, delivery_block: u64) {
+        self.origin.next_block = self.origin.next_block.max(dispatch_block);
+        self.destination.next_block = self.destination.next_block.max(delivery_block);
+    }
+
+    /// First Base block not scanned yet
+    pub fn next_dispatch_block(&self) -> u64 {
+        self.origin.next_block
+    }
+
+    /// First Celo block not scanned yet
+    pub fn next_delivery_block(&self) -> u64 {
+        self.destination.next_block
+    }
+
+    /// Record messages the bridge dispatched in newly confirmed Base
+    /// blocks. Returns the IDs of messages this completed.
+    pub async fn scan_dispatches(&mut self, base: &RpcClient, deliveries: &mut BTreeMap<B256, Delivery>) -> Result<Vec<B256>> {
+        self.scan(Side::Dispatch, base, deliveries).await
+    }
+
+    /// Record messages delivered to the vault in newly confirmed Celo
+    /// blocks. Returns the IDs of messages this completed.
+    pub async fn scan_deliveries(&mut self, celo: &RpcClient, deliveries: &mut BTreeMap<B256, Delivery>) -> Result<Vec<B256>> {
+        self.scan(Side::Delivery, celo, deliveries).await
+    }
+
+    /// Dry-run the delivery of `message` the way the Celo Mailbox makes it:
+    /// `ScholarFiVault.handle` called by the mailbox, without value as
+    /// relayers send none. `None` means it would go through.
+    pub async fn simulate(&self, celo: &RpcClient, message: &HyperlaneMessage) -> Result<Option<HandleRevert>> {
+        let call = ScholarFiVault::handleCall {
+            _origin: message.origin,
+            _sender: message.sender,
+            _messageBody: message.body.clone(),
+        };
+        match celo
+            .eth_call_from(self.destination.address, self.vault, call.abi_encode().into())
+            .await
+            .map_err(|e| e.name_revert(ScholarFiVaultErrors::name_by_selector))
+        {
+            Ok(_) => Ok(None),
+            Err(Error::Revert { error, data }) => Ok(Some(HandleRevert::from_revert(error, data))),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn scan(&mut self, side: Side, rpc: &RpcClient, deliveries: &mut BTreeMap<B256, Delivery>) -> Result<Vec<B256>> {
+        let (mailbox, topics) = match side {
+            Side::Dispatch => (&mut self.origin, vec![Dispatch::SIGNATURE_HASH]),
+            Side::Delivery => (&mut self.destination, vec![Process::SIGNATURE_HASH, ProcessId::SIGNATURE_HASH]),
+        };
+        let head = rpc.block_number().await?;
+        let Some(confirmed) = (head + 1).checked_sub(self.confirmations) else {
+            return Ok(Vec::new());
+        };
+        let mut completed = Vec::new();
+
+        while mailbox.next_block <= confirmed {
+            let to_block = confirmed.min(mailbox.next_block + self.page_size - 1);
+            debug!("Indexing Hyperlane messages at {} in blocks {}..={}", mailbox.address, mailbox.next_block, to_block);
+
+            let logs = rpc.get_logs(mailbox.address, &topics, mailbox.next_block, to_block).await?;
+            let messages = match side {
+                Side::Dispatch => dispatched(&logs, self.bridge, self.vault)?,
+                Side::Delivery => delivered(&logs, self.vault),
+            };
+            let mut timestamps = BTreeMap::new();
+            for Seen { id, block, child, message } in messages {
+                let timestamp = match timestamps.get(&block) {
+                    Some(&timestamp) => timestamp,
+                    None => {
+                        let header = rpc.block_header(block).await?.ok_or_else(|| {
+                            Error::Decode(format!("node has no block {} below its head {}", block, head))
+                        })?;
+                        *timestamps.entry(block).or_insert(header.timestamp.to())
+                    }
+                };
+                let sighting = Some(Sighting { block, timestamp });
+                let delivery = deliveries.entry(id).or_default();
+                let seen = match side {
+                    Side::Dispatch => &mut delivery.dispatched,
+                    Side::Delivery => &mut delivery.delivered,
+                };
+                if seen.is_some() {
+                    continue;
+                }
+                *seen = sighting;
+                delivery.child = delivery.child.or(child);
+                delivery.message = delivery.message.take().or(message);
+                match side {
+                    Side::Dispatch => info!("Hyperlane message {} dispatched at Base block {}", id, block),
+                    Side::Delivery => info!("Hyperlane message {} delivered at Celo block {}", id, block),
+                }
+                if delivery.latency().is_some() {
+                    completed.push(id);
+                }
+            }
+
+            mailbox.next_block = to_block + 1;
+        }
+
+        Ok(completed)
+    }
+}
+
+/// A message seen on one side, with what that side tells about it
+struct Seen {
+    id: B256,
+    block: u64,
+    child: Option<Address>,
+    message: Option<HyperlaneMessage>,
+}
+
+/// Messages `bridge` dispatched to `vault`, rebuilt from `Dispatch` logs
+fn dispatched(logs: &[Log], bridge: Address, vault: Address) -> Result<Vec<Seen>> {
+    let mut messages = Vec::new();
+    for log in logs {
+        if log.topics.get(1) != Some(&bridge.into_word()) {
+            continue;
+        }
+        let event = Dispatch::decode_raw_log(log.topics.iter().copied(), &log.data)?;
+        let message = HyperlaneMessage::decode(&event.message)?;
+        if message.recipient_address() != vault {
+            warn!(
+                "Hyperlane message {} from the bridge goes to {}, not the configured vault",
+                message.id(),
+                message.recipient_address()
+            );
+            continue;
+        }
+        let child = match DepositBody::decode(&message.body) {
+            Ok(body) => Some(body.child),
+            Err(e) => {
+                warn!("Hyperlane message {} from the bridge is not a deposit: {}", message.id(), e);
+                None
+            }
+        };
+        messages.push(Seen {
+            id: message.id(),
+            block: log.block_number.to(),
+            child,
+            message: Some(message),
+        });
+    }
+    Ok(messages)
+}
+
+/// Messages delivered to `vault`. `Process` names the recipient; the
+/// `ProcessId` right after it in the same transaction carries the ID.
+fn delivered(logs: &[Log], vault: Address) -> Vec<Seen> {
+    logs.windows(2)
+        .filter(|pair| {
+            let (process, id) = (&pair[0], &pair[1]);
+            process.topics.first() == Some(&Process::SIGNATURE_HASH)
+                && process.topics.get(3) == Some(&vault.into_word())
+                && id.topics.first() == Some(&ProcessId::SIGNATURE_HASH)
+                && id.transaction_hash == process.transaction_hash
+        })
+        .filter_map(|pair| {
+            Some(Seen {
+                id: *pair[1].topics.get(1)?,
+                block: pair[1].block_number.to(),
+                child: None,
+                message: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc::{reverted, MockChain, MockRpc, GENESIS_TIMESTAMP};
+    use alloy_primitives::{address, keccak256, Bytes, U256};
+    use alloy_sol_types::{SolError, SolValue};
+    use serde_json::json;
+
+    const BASE_MAILBOX: Address = address!("6966b0E55883d49BFB24539356a2f8A673E02039");
+    const CELO_MAILBOX: Address = address!("D0680F80F4f947968206806C2598Cbc5b6FE5b03");
+    const BRIDGE: Address = address!("00000000000000000000000000000000000000b1");
+    const VAULT: Address = address!("00000000000000000000000000000000000000aa");
+    const STRANGER: Address = address!("00000000000000000000000000000000000000f0");
+
+    const CHILD: Address = address!("0000000000000000000000000000000000000001");
+
+    /// `Dispatch` of a deposit for CHILD from `sender` in block `number`.
+    /// Returns the message ID.
+    fn dispatch(chain: &MockChain, number: u64, sender: Address, nonce: u32) -> B256 {
+        let message = HyperlaneMessage {
+            version: crate::message::VERSION,
+            nonce,
+            origin: 84532,
+            sender: sender.into_word(),
+            destination: 11142220,
+            recipient: VAULT.into_word(),
+            body: (CHILD, STRANGER).abi_encode_params().into(),
+        };
+        let data = Dispatch {
+            sender,
+            destination: message.destination,
+            recipient: message.recipient,
+            message: message.encode().into(),
+        }
+        .encode_log_data();
+        chain.add_log(
+            number,
+            json!({
+                "address": BASE_MAILBOX,
+                "topics": data.topics(),
+                "data": data.data,
+                "transactionHash": B256::with_last_byte(nonce as u8),
+                "logIndex": "0x0",
+            }),
+        );
+        message.id()
+    }
+
+    /// `Process` + `ProcessId` to `recipient` in block `number`
+    fn process(chain: &MockChain, number: u64, recipient: Address, id: B256) {
+        let tx = keccak256(id);
+        let process = Process {
+            origin: 84532,
+            sender: BRIDGE.into_word(),
+            recipient,
+        }
+        .encode_log_data();
+        let id = ProcessId { messageId: id }.encode_log_data();
+        for (data, index) in [(process, 0), (id, 1)] {
+            chain.add_log(
+                number,
+                json!({
+                    "address": CELO_MAILBOX,
+                    "topics": data.topics(),
+                    "data": data.data,
+                    "transactionHash": tx,
+                    "logIndex": U256::from(index),
+                }),
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn matches_dispatches_with_deliveries() {
+        let base = MockChain::start(40).await;
+        let first = dispatch(&base, 10, BRIDGE, 1);
+        let foreign = dispatch(&base, 12, STRANGER, 2);
+        let pending = dispatch(&base, 15, BRIDGE, 3);
+        let celo = MockChain::start(100).await;
+        process(&celo, 70, VAULT, first);
+        process(&celo, 71, STRANGER, foreign);
+        // Delivered, but its dispatch is not confirmed on Base yet
+        let early = dispatch(&base, 39, BRIDGE, 4);
+        process(&celo, 90, VAULT, early);
+        let base_rpc = RpcClient::new(reqwest::Client::new(), base.url());
+        let celo_rpc = RpcClient::new(reqwest::Client::new(), celo.url());
+        let mut tracker = DeliveryTracker::new(
+            BRIDGE,
+            VAULT,
+            Mailbox { address: BASE_MAILBOX, next_block: 0 },
+            Mailbox { address: CELO_MAILBOX, next_block: 0 },
+            16,
+            5,
+        );
+        let mut deliveries = BTreeMap::new();
+
+        assert!(tracker.scan_dispatches(&base_rpc, &mut deliveries).await.unwrap().is_empty());
+        assert_eq!(tracker.next_dispatch_block(), 37);
+        let completed = tracker.scan_deliveries(&celo_rpc, &mut deliveries).await.unwrap();
+        assert_eq!(completed, vec![first]);
+        assert_eq!(tracker.next_delivery_block(), 97);
+
+        assert_eq!(deliveries.len(), 3, "{deliveries:?}");
+        assert_eq!(deliveries[&first].latency(), Some(60));
+        assert_eq!(deliveries[&first].child, Some(CHILD));
+        let undelivered = &deliveries[&pending];
+        assert_eq!(undelivered.dispatched.unwrap().timestamp, GENESIS_TIMESTAMP + 15);
+        assert_eq!(undelivered.message.as_ref().map(HyperlaneMessage::id), Some(pending));
+        assert!(!undelivered.is_overdue(GENESIS_TIMESTAMP + 75, 60));
+        assert!(undelivered.is_overdue(GENESIS_TIMESTAMP + 76, 60));
+        assert_eq!(deliveries[&early].dispatched, None);
+
+        // Once Base confirms the dispatch, the early delivery completes
+        base.reorg(41, 45);
+        let completed = tracker.scan_dispatches(&base_rpc, &mut deliveries).await.unwrap();
+        assert_eq!(completed, vec![early]);
+        assert_eq!(deliveries[&early].latency(), Some(51));
+    }
+
+    #[tokio::test]
+    async fn classifies_handle_reverts() {
+        let adopted = address!("0000000000000000000000000000000000000002");
+        let orphan = address!("0000000000000000000000000000000000000003");
+        let celo = MockRpc::start(move |method, params| {
+            assert_eq!(method, "eth_call");
+            assert_eq!(params[0]["from"], json!(CELO_MAILBOX));
+            assert_eq!(params[0]["to"], json!(VAULT));
+            assert_eq!(params[0].get("value"), None);
+            let data: Bytes = serde_json::from_value(params[0]["data"].clone()).unwrap();
+            let call = ScholarFiVault::handleCall::abi_decode(&data).unwrap();
+            let selector = match DepositBody::decode(&call._messageBody).unwrap().child {
+                CHILD => ScholarFiVault::AccountNotFound::SELECTOR,
+                child if child == adopted => return Ok(json!("0x")),
+                _ => [0xde, 0xad, 0xbe, 0xef],
+            };
+            Err(reverted(selector))
+        })
+        .await;
+        let celo = RpcClient::new(reqwest::Client::new(), celo.url());
+        let tracker = DeliveryTracker::new(
+            BRIDGE,
+            VAULT,
+            Mailbox { address: BASE_MAILBOX, next_block: 0 },
+            Mailbox { address: CELO_MAILBOX, next_block: 0 },
+            16,
+            5,
+        );
+        let message = |child: Address| HyperlaneMessage {
+            version: crate::message::VERSION,
+            nonce: 7,
+            origin: 84532,
+            sender: BRIDGE.into_word(),
+            destination: 11142220,
+            recipient: VAULT.into_word(),
+            body: (child, STRANGER).abi_encode_params().into(),
+        };
+
+        let revert = tracker.simulate(&celo, &message(CHILD)).await.unwrap();
+        assert_eq!(revert, Some(HandleRevert::AccountNotFound));
+        let remediation = revert.unwrap().remediation(&message(CHILD));
+        assert!(remediation.contains(&format!("parent {} must create", STRANGER)), "{remediation}");
+        assert!(remediation.contains(&format!("createChildAccount({})", CHILD)), "{remediation}");
+        assert_eq!(tracker.simulate(&celo, &message(adopted)).await.unwrap(), None);
+        let unknown = tracker.simulate(&celo, &message(orphan)).await.unwrap();
+        let unknown = unknown.unwrap();
+        assert_eq!(unknown.to_string(), "unknown error 0xdeadbeef");
+        let remediation = unknown.remediation(&message(orphan));
+        assert!(remediation.contains("(data 0xdeadbeef)"), "{remediation}");
+    }
+}
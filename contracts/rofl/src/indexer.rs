@@ -0,0 +1,274 @@
+use crate::contracts::ScholarFiVault::ChildAccountCreated;
+use crate::error::Result;
+use crate::reorg::BlockTracker;
+use crate::rpc::RpcClient;
+use alloy_primitives::{Address, B256};
+use alloy_sol_types::SolEvent;
+use log::{debug, info, warn};
+use std::collections::BTreeMap;
+
+/// Child discovery for ScholarFiVault
+///
+/// The vault has no on-chain enumeration of children, so we learn about
+/// accounts from `ChildAccountCreated` logs. Starting at the vault's
+/// deployment block, `sync` pages through `eth_getLogs` in fixed-size block
+/// ranges up to the chain head and remembers every child it has seen.
+///
+/// Blocks within the confirmation depth of the head can still be replaced.
+/// Their hashes are tracked, and when the chain reorganizes the indexer
+/// forgets every child created after the common ancestor and indexes the
+/// new blocks from there.
+pub struct ChildIndexer {
+    vault: Address,
+    next_block: u64,
+    page_size: u64,
+    /// Child address and the block it was created at
+    children: BTreeMap<Address, u64>,
+    blocks: BlockTracker,
+}
+
+/// What one `sync` changed
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub discovered: usize,
+    /// Children whose creation was reorganized away
+    pub removed: Vec<Address>,
+    /// Deepest common ancestor rolled back to, if the chain reorganized
+    pub ancestor: Option<u64>,
+}
+
+impl ChildIndexer {
+    pub fn new(vault: Address, deployment_block: u64, page_size: u64, confirmation_depth: u64) -> Self {
+        Self {
+            vault,
+            next_block: deployment_block,
+            page_size: page_size.max(1),
+            children: BTreeMap::new(),
+            blocks: BlockTracker::new(confirmation_depth),
+        }
+    }
+
+    /// Resume from a persisted checkpoint
+    pub fn restore(
+        &mut self,
+        next_block: u64,
+        children: impl IntoIterator<Item = (Address, u64)>,
+        block_hashes: BTreeMap<u64, B256>,
+    ) {
+        self.next_block = self.next_block.max(next_block);
+        self.children.extend(children);
+        self.blocks.restore(block_hashes);
+    }
+
+    /// First block not indexed yet
+    pub fn next_block(&self) -> u64 {
+        self.next_block
+    }
+
+    /// Known children and their creation blocks, in address order
+    pub fn children(&self) -> &BTreeMap<Address, u64> {
+        &self.children
+    }
+
+    /// Tracked hashes of recent blocks
+    pub fn block_hashes(&self) -> &BTreeMap<u64, B256> {
+        self.blocks.hashes()
+    }
+
+    /// Learn about a child outside `sync`, e.g. from a live event seen at
+    /// `block`. Returns whether it was new.
+    pub fn insert(&mut self, child: Address, block: u64) -> bool {
+        if self.children.contains_key(&child) {
+            return false;
+        }
+        self.children.insert(child, block);
+        true
+    }
+
+    /// Check recent blocks for reorgs, then index all new
+    /// `ChildAccountCreated` logs up to the current head.
+    ///
+    /// Progress is kept page by page, so a failure midway resumes from the
+    /// first unfinished range on the next call.
+    pub async fn sync(&mut self, rpc: &RpcClient) -> Result<SyncReport> {
+        let head = rpc.block_number().await?;
+        let mut report = SyncReport::default();
+
+        while let Some(ancestor) = self.blocks.advance(rpc, head).await? {
+            report.removed.extend(self.rollback(ancestor));
+            report.ancestor = Some(report.ancestor.map_or(ancestor, |deepest| deepest.min(ancestor)));
+        }
+
+        'pages: while self.next_block <= head {
+            let to_block = head.min(self.next_block + self.page_size - 1);
+            debug!("Indexing ChildAccountCreated in blocks {}..={}", self.next_block, to_block);
+
+            let logs = rpc
+                .get_logs(self.vault, &[ChildAccountCreated::SIGNATURE_HASH], self.next_block, to_block)
+                .await?;
+
+            // Logs from a block we no longer track the hash of mean the chain
+            // moved since `advance`; keep the page for the next sync to redo
+            let mut page = Vec::with_capacity(logs.len());
+            for log in logs {
+                let block = log.block_number.to::<u64>();
+                if let (Some(hash), Some(tracked)) = (log.block_hash, self.blocks.hash(block)) {
+                    if hash != tracked {
+                        warn!("Log in block {} has hash {}, expected {}; retrying next sync", block, hash, tracked);
+                        break 'pages;
+                    }
+                }
+                page.push((ChildAccountCreated::decode_raw_log(log.topics.iter().copied(), &log.data)?, block));
+            }
+
+            for (event, block) in page {
+                if self.insert(event.child, block) {
+                    info!("Discovered child {} (parent {}) at block {}", event.child, event.parent, block);
+                    report.discovered += 1;
+                }
+            }
+
+            self.next_block = to_block + 1;
+        }
+
+        Ok(report)
+    }
+
+    /// Forget children created after `ancestor` and re-index from the block
+    /// after it. Returns the forgotten children.
+    fn rollback(&mut self, ancestor: u64) -> Vec<Address> {
+        let removed: Vec<Address> = self
+            .children
+            .iter()
+            .filter(|(_, &block)| block > ancestor)
+            .map(|(&child, _)| child)
+            .collect();
+        for child in &removed {
+            self.children.remove(child);
+            warn!("Child {} was created in a reorganized block; dropping it", child);
+        }
+        self.next_block = self.next_block.min(ancestor + 1);
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::mock_rpc::{MockChain, MockRpc};
+    use alloy_primitives::{address, U256, U64};
+    use alloy_sol_types::SolEvent;
+    use serde_json::{json, Value};
+
+    const VAULT: Address = address!("00000000000000000000000000000000000000aa");
+    const PARENT: Address = address!("00000000000000000000000000000000000000bb");
+
+    fn created_log(child: Address, block: u64) -> Value {
+        let event = ChildAccountCreated {
+            child,
+            parent: PARENT,
+            timestamp: U256::from(block),
+        };
+        let data = event.encode_log_data();
+        json!({
+            "address": VAULT,
+            "topics": data.topics(),
+            "data": data.data,
+            "blockNumber": U64::from(block),
+            "blockHash": format!("0x{:064x}", block),
+            "transactionHash": format!("0x{:064x}", block + 1000),
+            "logIndex": "0x0",
+        })
+    }
+
+    #[tokio::test]
+    async fn sync_pages_from_deployment_block_to_head() {
+        let alice = address!("0000000000000000000000000000000000000001");
+        let bob = address!("0000000000000000000000000000000000000002");
+        let node = MockRpc::start(move |method, params| match method {
+            "eth_blockNumber" => Ok(json!("0x82")), // 130
+            "eth_getBlockByNumber" => {
+                let number: U64 = serde_json::from_value(params[0].clone()).unwrap();
+                let hash = |n: u64| format!("0x{:064x}", n);
+                Ok(json!({ "number": number, "hash": hash(number.to()), "parentHash": hash(number.to::<u64>() - 1), "timestamp": number }))
+            }
+            "eth_getLogs" => {
+                let from: U64 = serde_json::from_value(params[0]["fromBlock"].clone()).unwrap();
+                let logs: Vec<Value> = [(alice, 105u64), (bob, 125), (alice, 126)]
+                    .into_iter()
+                    .filter(|(_, block)| *block >= from.to::<u64>() && *block < from.to::<u64>() + 10)
+                    .map(|(child, block)| created_log(child, block))
+                    .collect();
+                Ok(Value::Array(logs))
+            }
+            other => panic!("unexpected method {other}"),
+        })
+        .await;
+        let rpc = RpcClient::new(reqwest::Client::new(), node.url());
+        let mut indexer = ChildIndexer::new(VAULT, 100, 10, 4);
+
+        assert_eq!(indexer.sync(&rpc).await.unwrap().discovered, 2);
+        assert_eq!(indexer.children().keys().copied().collect::<Vec<_>>(), vec![alice, bob]);
+        assert_eq!(indexer.children()[&alice], 105, "first sighting is kept");
+
+        let ranges: Vec<(String, String)> = node
+            .calls()
+            .into_iter()
+            .filter(|(method, _)| method == "eth_getLogs")
+            .map(|(_, p)| (p[0]["fromBlock"].as_str().unwrap().into(), p[0]["toBlock"].as_str().unwrap().into()))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                ("0x64".into(), "0x6d".into()),
+                ("0x6e".into(), "0x77".into()),
+                ("0x78".into(), "0x81".into()),
+                ("0x82".into(), "0x82".into()),
+            ]
+        );
+
+        // Already at head: only the head and the tracked tip are queried
+        let calls = node.calls().len();
+        assert_eq!(indexer.sync(&rpc).await.unwrap(), SyncReport::default());
+        let methods: Vec<String> = node.calls().into_iter().skip(calls).map(|(method, _)| method).collect();
+        assert_eq!(methods, ["eth_blockNumber", "eth_getBlockByNumber"]);
+    }
+
+    #[tokio::test]
+    async fn reorg_rolls_back_to_common_ancestor_and_reindexes() {
+        let alice = address!("0000000000000000000000000000000000000001");
+        let bob = address!("0000000000000000000000000000000000000002");
+        let carol = address!("0000000000000000000000000000000000000003");
+        let chain = MockChain::start(20).await;
+        chain.add_log(5, created_log(alice, 5));
+        chain.add_log(18, created_log(bob, 18));
+        let rpc = RpcClient::new(reqwest::Client::new(), chain.url());
+        let mut indexer = ChildIndexer::new(VAULT, 0, 8, 6);
+
+        assert_eq!(indexer.sync(&rpc).await.unwrap().discovered, 2);
+        assert_eq!(indexer.block_hashes().keys().copied().collect::<Vec<_>>(), (15..=20).collect::<Vec<_>>());
+
+        // Blocks 17.. are replaced: Bob's creation is gone, Carol's is new
+        chain.reorg(17, 22);
+        chain.add_log(21, created_log(carol, 21));
+
+        let report = indexer.sync(&rpc).await.unwrap();
+        assert_eq!(
+            report,
+            SyncReport {
+                discovered: 1,
+                removed: vec![bob],
+                ancestor: Some(16),
+            }
+        );
+        assert_eq!(indexer.children().clone(), BTreeMap::from([(alice, 5), (carol, 21)]));
+        assert_eq!(indexer.next_block(), 23);
+        assert_eq!(indexer.block_hashes().get(&22), Some(&chain.hash(22)));
+
+        // Deeper than the confirmation depth: refuse rather than guess
+        chain.reorg(3, 22);
+        let err = indexer.sync(&rpc).await.unwrap_err();
+        assert!(matches!(err, Error::DeepReorg { depth: 6 }), "{err}");
+    }
+}
@@ -0,0 +1,262 @@
+use crate::fixed_point::{Ray, Rounding, Wad};
+use alloy_primitives::{Address, U256};
+use std::collections::HashMap;
+use std::fmt;
+
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// Per-child growth accrual ledger
+///
+/// `ChildDataStore.updateVaultGrowth` replaces the stored value, so the
+/// monitor has to send the running total, not the latest increment. The
+/// ledger keeps that total per child together with when it was last accrued,
+/// so each cycle accrues exactly the time that has really elapsed.
+///
+/// Accruing is two-step: `accrue` computes the new total without touching
+/// the ledger, and `commit` applies it once the on-chain write succeeded.
+/// A failed write therefore leaves the elapsed time to be picked up next cycle.
+#[derive(Debug, Default)]
+pub struct GrowthLedger {
+    entries: HashMap<Address, LedgerEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LedgerEntry {
+    /// Cumulative growth in wei, as last written on-chain
+    pub total_growth: u128,
+    /// Unix timestamp growth has been accrued up to
+    pub last_accrued: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccrualError {
+    /// The ledger has no entry for the child yet
+    Untracked(Address),
+    /// Growth does not fit the fixed-point or wei range
+    Overflow(Address),
+}
+
+impl fmt::Display for AccrualError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccrualError::Untracked(child) => write!(f, "growth for {} is not tracked yet", child),
+            AccrualError::Overflow(child) => write!(f, "growth for {} overflowed", child),
+        }
+    }
+}
+
+impl std::error::Error for AccrualError {}
+
+/// Growth accrued for one child between its last accrual and `accrued_at`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Accrual {
+    pub child: Address,
+    pub accrued: u128,
+    pub total: u128,
+    pub elapsed: u64,
+    pub accrued_at: u64,
+}
+
+impl GrowthLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tracked children and their last committed growth
+    pub fn entries(&self) -> impl Iterator<Item = (Address, LedgerEntry)> + '_ {
+        self.entries.iter().map(|(child, entry)| (*child, *entry))
+    }
+
+    /// Start tracking `child` from an existing total, accruing from `now`
+    pub fn open(&mut self, child: Address, total_growth: u128, now: u64) {
+        self.entries.insert(
+            child,
+            LedgerEntry {
+                total_growth,
+                last_accrued: now,
+            },
+        );
+    }
+
+    /// Growth on `vault_amount` wei compounding every second at
+    /// `rate_per_second` for the time since the last accrual, rounded down
+    /// to whole wei
+    pub fn accrue(
+        &self,
+        child: Address,
+        vault_amount: u128,
+        rate_per_second: Ray,
+        now: u64,
+    ) -> Result<Accrual, AccrualError> {
+        let entry = self.entries.get(&child).ok_or(AccrualError::Untracked(child))?;
+        let elapsed = now.saturating_sub(entry.last_accrued);
+
+        let accrued = growth(vault_amount, rate_per_second, elapsed).ok_or(AccrualError::Overflow(child))?;
+        let total = entry
+            .total_growth
+            .checked_add(accrued)
+            .ok_or(AccrualError::Overflow(child))?;
+
+        Ok(Accrual {
+            child,
+            accrued,
+            total,
+            elapsed,
+            accrued_at: now,
+        })
+    }
+
+    /// Stop tracking `child`, e.g. when the block that created it was
+    /// reorganized away
+    pub fn remove(&mut self, child: Address) -> Option<LedgerEntry> {
+        self.entries.remove(&child)
+    }
+
+    /// Apply an accrual once its total has been written on-chain
+    pub fn commit(&mut self, accrual: &Accrual) {
+        self.entries.insert(
+            accrual.child,
+            LedgerEntry {
+                total_growth: accrual.total,
+                last_accrued: accrual.accrued_at,
+            },
+        );
+    }
+}
+
+/// `vault_amount * ((1 + rate)^seconds - 1)`, computed in ray and rounded
+/// down to wei
+fn growth(vault_amount: u128, rate_per_second: Ray, seconds: u64) -> Option<u128> {
+    let factor = rate_per_second.compound(seconds)?.checked_sub(Ray::ONE)?;
+    let balance = Wad::from_raw(U256::from(vault_amount)).to_ray()?;
+    let growth = balance.checked_mul(factor, Rounding::Down)?.to_wad(Rounding::Down);
+    growth.raw().try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    const CHILD: Address = address!("0000000000000000000000000000000000000001");
+    const ONE_CELO: u128 = 1_000_000_000_000_000_000;
+
+    /// The largest per-second rate `r` with `(1 + r)^year <= 1 + apy`,
+    /// found by bisection
+    fn rate(apy_bps: u64) -> Ray {
+        let target = Ray::ONE.checked_add(Ray::from_bps(apy_bps)).unwrap();
+
+        // (1 + r)^n >= 1 + n·r, so apy / n is an upper bound
+        let mut low = U256::ZERO;
+        let mut high = Ray::from_bps(apy_bps).raw() / U256::from(SECONDS_PER_YEAR) + U256::from(1u8);
+        while low < high {
+            let mid = low + (high - low + U256::from(1u8)) / U256::from(2u8);
+            match Ray::from_raw(mid).compound(SECONDS_PER_YEAR) {
+                Some(factor) if factor <= target => low = mid,
+                _ => high = mid - U256::from(1u8),
+            }
+        }
+        Ray::from_raw(low)
+    }
+
+    /// Within a millionth of a CELO (rounding the per-second rate down
+    /// costs a few wei per year)
+    fn assert_close(actual: u128, expected: u128) {
+        assert!(actual.abs_diff(expected) < 1_000_000_000_000, "{} vs {}", actual, expected);
+    }
+
+    #[test]
+    fn rate_is_largest_underestimate() {
+        for bps in [1, 400, 9_999] {
+            let target = Ray::ONE.checked_add(Ray::from_bps(bps)).unwrap();
+            let rate = rate(bps);
+            assert!(rate.compound(SECONDS_PER_YEAR).unwrap() <= target);
+            let next = Ray::from_raw(rate.raw() + U256::from(1u8));
+            assert!(next.compound(SECONDS_PER_YEAR).unwrap() > target);
+        }
+    }
+
+    #[test]
+    fn accrues_for_real_elapsed_time() {
+        let mut ledger = GrowthLedger::new();
+        ledger.open(CHILD, 0, 1_000);
+
+        // A full year at 4% APY on 1 CELO
+        let accrual = ledger
+            .accrue(CHILD, ONE_CELO, rate(400), 1_000 + SECONDS_PER_YEAR)
+            .unwrap();
+        assert_eq!(accrual.elapsed, SECONDS_PER_YEAR);
+        assert!(accrual.accrued <= ONE_CELO / 25);
+        assert_close(accrual.accrued, ONE_CELO / 25);
+
+        // Half a year compounds to sqrt(1.04) - 1, not 2%
+        let half = ledger
+            .accrue(CHILD, ONE_CELO, rate(400), 1_000 + SECONDS_PER_YEAR / 2)
+            .unwrap();
+        assert_close(half.accrued, 19_803_902_718_556_966);
+    }
+
+    #[test]
+    fn totals_accumulate_across_commits() {
+        let mut ledger = GrowthLedger::new();
+        ledger.open(CHILD, 7, 0);
+
+        let first = ledger.accrue(CHILD, ONE_CELO, rate(1_000), SECONDS_PER_YEAR).unwrap();
+        ledger.commit(&first);
+        let second = ledger.accrue(CHILD, ONE_CELO, rate(1_000), 2 * SECONDS_PER_YEAR).unwrap();
+
+        assert_eq!(first.total, 7 + first.accrued);
+        assert_eq!(second.accrued, first.accrued);
+        assert_eq!(second.total, 7 + 2 * first.accrued);
+    }
+
+    #[test]
+    fn uncommitted_accrual_keeps_elapsed_time() {
+        let mut ledger = GrowthLedger::new();
+        ledger.open(CHILD, 0, 0);
+
+        // First write failed, so nothing was committed
+        let failed = ledger.accrue(CHILD, ONE_CELO, rate(1_000), 100).unwrap();
+        let retried = ledger.accrue(CHILD, ONE_CELO, rate(1_000), 200).unwrap();
+
+        assert_eq!(failed.elapsed, 100);
+        assert_eq!(retried.elapsed, 200);
+        assert_eq!(retried.total, retried.accrued);
+    }
+
+    #[test]
+    fn keeps_precision_above_f64_range() {
+        let mut ledger = GrowthLedger::new();
+        ledger.open(CHILD, 0, 0);
+
+        // 100% for one second doubles the balance exactly; this amount is
+        // not representable as f64, which is off by over 5000 wei
+        let amount = 123_456_789_012_345_678_901;
+        let accrual = ledger.accrue(CHILD, amount, Ray::ONE, 1).unwrap();
+        assert_eq!(accrual.accrued, amount);
+    }
+
+    #[test]
+    fn unknown_child_and_clock_skew() {
+        let mut ledger = GrowthLedger::new();
+        assert_eq!(
+            ledger.accrue(CHILD, ONE_CELO, rate(500), 10),
+            Err(AccrualError::Untracked(CHILD))
+        );
+
+        ledger.open(CHILD, 0, 100);
+        let accrual = ledger.accrue(CHILD, ONE_CELO, rate(500), 50).unwrap();
+        assert_eq!(accrual.elapsed, 0);
+        assert_eq!(accrual.accrued, 0);
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        let mut ledger = GrowthLedger::new();
+        ledger.open(CHILD, u128::MAX, 0);
+        assert_eq!(
+            ledger.accrue(CHILD, ONE_CELO, rate(500), 10),
+            Err(AccrualError::Overflow(CHILD))
+        );
+    }
+}
@@ -1,9 +1,71 @@
-use log::{info, error};
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_sol_types::SolCall;
+use log::{info, error, warn};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
 use tokio::time;
 
+mod aave;
+mod config;
+mod contracts;
+mod data_store;
+mod deployment;
+mod deposits;
+mod error;
+mod fees;
+mod fixed_point;
+mod health;
+mod hyperlane;
+mod indexer;
+mod ledger;
+mod message;
+mod metrics;
+mod multicall;
+mod network;
+mod reconcile;
+mod reorg;
+mod retry;
+mod rpc;
+mod server;
+mod signer;
+mod state;
+mod subscription;
+mod tx;
+mod verification;
+
+#[cfg(test)]
+mod mock_rpc;
+
+use aave::{AaveReserve, ReserveRate};
+use config::MonitoringConfig;
+use contracts::{ScholarFiAgeVerifier, ScholarFiVault};
+use data_store::DataStore;
+use deposits::{Deposit, DepositKey, DepositSource, DepositWatcher};
+use error::{Error, Recovery, Result};
+use fees::FeeQuoter;
+use indexer::ChildIndexer;
+use fixed_point::Ray;
+use health::ReadinessProbe;
+use hyperlane::{Delivery, DeliveryTracker, HandleRevert, Mailbox};
+use ledger::{AccrualError, GrowthLedger};
+use message::HyperlaneMessage;
+use metrics::Metrics;
+use multicall::Multicall;
+use network::{ChainProfile, Network};
+use reconcile::{ReconciliationReport, ReportBuilder, ReportFormat};
+use rpc::RpcClient;
+use server::StatusServer;
+use signer::Signer;
+use state::{MonitorState, StateStore};
+use subscription::{VaultEvent, VaultEventKind, VaultSubscription};
+use std::sync::Arc;
+use tx::TxSender;
+use verification::{Verification, VerificationWatcher};
+
 /// Scholar-Fi ROFL Monitoring Service
 ///
 /// This is a SIMPLIFIED version for hackathon demonstration.
@@ -21,154 +83,1292 @@ use tokio::time;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct VaultBalance {
-    child_address: String,
+    child_address: Address,
     vault_amount: u128,
     spending_amount: u128,
     is_verified: bool,
 }
 
-#[derive(Debug)]
-struct MonitoringConfig {
-    celo_rpc: String,
-    oasis_rpc: String,
-    scholar_fi_vault_address: String,
-    child_data_store_address: String,
-    check_interval_seconds: u64,
-}
+/// APY below which the monitor flags a rebalancing opportunity (2.0%)
+const APY_THRESHOLD_BPS: u64 = 200;
 
-impl Default for MonitoringConfig {
-    fn default() -> Self {
-        Self {
-            celo_rpc: std::env::var("CELO_RPC_URL")
-                .unwrap_or_else(|_| "https://alfajores-forno.celo-testnet.org".to_string()),
-            oasis_rpc: std::env::var("SAPPHIRE_TESTNET_RPC")
-                .unwrap_or_else(|_| "https://testnet.sapphire.oasis.io".to_string()),
-            scholar_fi_vault_address: std::env::var("SCHOLAR_FI_VAULT")
-                .unwrap_or_else(|_| "0x0000000000000000000000000000000000000000".to_string()),
-            child_data_store_address: std::env::var("CHILD_DATA_STORE")
-                .unwrap_or_else(|_| "0x0000000000000000000000000000000000000000".to_string()),
-            check_interval_seconds: std::env::var("CHECK_INTERVAL")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(3600), // Default: check every hour
-        }
-    }
-}
+/// Chain names, used as RPC metric labels. `CELO` is also the checkpoint
+/// key for the Celo log indexer.
+const CELO: &str = "celo";
+const OASIS: &str = "oasis";
+const BASE: &str = "base";
+
+/// Checkpoint keys for the deposit watchers
+const CELO_DEPOSITS: &str = "celo:deposits";
+const BASE_DEPOSITS: &str = "base:deposits";
+
+/// Checkpoint key for the age verification watcher
+const CELO_VERIFICATIONS: &str = "celo:verifications";
+
+/// Checkpoint keys for the Hyperlane delivery tracker
+const BASE_DISPATCHES: &str = "base:dispatches";
+const CELO_DELIVERIES: &str = "celo:deliveries";
+
+/// How often to poll for a submitted transaction's receipt
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Delay before re-running a cycle that hit a retryable error
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(15);
+
+/// Per-request timeout for `/readyz` RPC probes
+const READINESS_RPC_TIMEOUT: Duration = Duration::from_secs(5);
 
 struct RoflMonitor {
     config: MonitoringConfig,
-    client: Client,
+    vault: Address,
+    celo: RpcClient,
+    /// Batches per-child vault reads; `None` reads them one by one
+    multicall: Option<Multicall>,
+    indexer: ChildIndexer,
+    /// Base, for ParentDepositSplitter deposits
+    base: RpcClient,
+    vault_deposits: DepositWatcher,
+    splitter_deposits: Option<DepositWatcher>,
+    /// Every deposit seen, keyed by (chain, tx hash, log index)
+    deposits: BTreeMap<DepositKey, Deposit>,
+    verification_watcher: VerificationWatcher,
+    /// Children seen age-verified on Celo
+    verifications: BTreeMap<Address, Verification>,
+    /// Last time Celo verification was reconciled with Oasis
+    age_reconciled_at: Option<Instant>,
+    /// Follows ScholarFiBridge messages; `None` without a bridge
+    delivery_tracker: Option<DeliveryTracker>,
+    /// Bridged deposits' Hyperlane messages, by message ID
+    deliveries: BTreeMap<B256, Delivery>,
+    /// Pending messages `ScholarFiVault.handle` would reject, and why
+    rejections: BTreeMap<B256, HandleRevert>,
+    data_store: DataStore,
+    aave: AaveReserve,
+    ledger: GrowthLedger,
+    state: StateStore,
+    metrics: Arc<Metrics>,
 }
 
 impl RoflMonitor {
-    fn new(config: MonitoringConfig) -> Self {
-        Self {
+    fn new(config: MonitoringConfig) -> Result<Self> {
+        let client = Client::new();
+        let metrics = Arc::new(Metrics::new());
+        let retry = config.retry_policy();
+        let vault = config.scholar_fi_vault_address;
+
+        let sender = match &config.rofl_private_key {
+            Some(key) => Some(TxSender::new(
+                Signer::from_hex(key.expose())?,
+                Duration::from_secs(config.tx_timeout_seconds),
+                RECEIPT_POLL_INTERVAL,
+            )),
+            None => None,
+        };
+        let data_store = DataStore::new(
+            config.child_data_store_address,
+            RpcClient::failover(client.clone(), config.oasis_rpc.clone())
+                .with_retry(retry)
+                .with_metrics(metrics.clone(), OASIS),
+            sender,
+        );
+
+        // Resume from the last committed cycle
+        let state = StateStore::open(&config.state_path)?;
+        let saved = state.load()?;
+
+        let mut indexer = ChildIndexer::new(
+            vault,
+            config.vault_deployment_block,
+            config.log_page_size,
+            config.confirmation_depth,
+        );
+        indexer.restore(
+            saved.checkpoints.get(CELO).copied().unwrap_or_default(),
+            saved.children.clone(),
+            saved.block_hashes.get(CELO).cloned().unwrap_or_default(),
+        );
+
+        let checkpoint = |key: &str| saved.checkpoints.get(key).copied().unwrap_or_default();
+        let mut vault_deposits = DepositWatcher::new(
+            CELO,
+            vault,
+            DepositSource::Vault,
+            config.vault_deployment_block,
+            config.log_page_size,
+            config.confirmation_depth,
+        );
+        vault_deposits.restore(checkpoint(CELO_DEPOSITS));
+        let splitter_deposits = config.deposit_splitter_address.map(|splitter| {
+            let mut watcher = DepositWatcher::new(
+                BASE,
+                splitter,
+                DepositSource::Splitter,
+                config.splitter_deployment_block,
+                config.log_page_size,
+                config.confirmation_depth,
+            );
+            watcher.restore(checkpoint(BASE_DEPOSITS));
+            watcher
+        });
+
+        let mut verification_watcher = VerificationWatcher::new(
+            vault,
+            config.age_verifier_address,
+            config.vault_deployment_block,
+            config.log_page_size,
+            config.confirmation_depth,
+        );
+        verification_watcher.restore(checkpoint(CELO_VERIFICATIONS));
+
+        // Config validation guarantees both Mailboxes when there is a bridge
+        let delivery_tracker = match (
+            config.bridge_address,
+            config.base_mailbox_address,
+            config.celo_mailbox_address,
+        ) {
+            (Some(bridge), Some(origin), Some(destination)) => {
+                let mut tracker = DeliveryTracker::new(
+                    bridge,
+                    vault,
+                    Mailbox {
+                        address: origin,
+                        next_block: config.bridge_deployment_block,
+                    },
+                    Mailbox {
+                        address: destination,
+                        next_block: config.vault_deployment_block,
+                    },
+                    config.log_page_size,
+                    config.confirmation_depth,
+                );
+                tracker.restore(checkpoint(BASE_DISPATCHES), checkpoint(CELO_DELIVERIES));
+                Some(tracker)
+            }
+            _ => None,
+        };
+
+        let mut ledger = GrowthLedger::new();
+        for (child, entry) in &saved.growth {
+            ledger.open(*child, entry.total_growth, entry.last_accrued);
+        }
+
+        if let Some(sender) = data_store.sender() {
+            sender.restore_pending(saved.pending_txs);
+        }
+
+        Ok(Self {
+            vault,
+            celo: RpcClient::failover(client.clone(), config.celo_rpc.clone())
+                .with_retry(retry)
+                .with_quorum(config.celo_quorum as usize)
+                .with_metrics(metrics.clone(), CELO),
+            multicall: config
+                .multicall_address
+                .filter(|_| config.multicall_batch_size > 0)
+                .map(|address| Multicall::new(address, config.multicall_batch_size as usize)),
+            indexer,
+            base: RpcClient::failover(client, config.base_rpc.clone())
+                .with_retry(retry)
+                .with_metrics(metrics.clone(), BASE),
+            vault_deposits,
+            splitter_deposits,
+            deposits: saved.deposits,
+            verification_watcher,
+            verifications: saved.verifications,
+            age_reconciled_at: None,
+            delivery_tracker,
+            deliveries: saved.deliveries,
+            rejections: BTreeMap::new(),
+            data_store,
+            aave: AaveReserve::new(config.aave_data_provider_address, config.aave_asset_address),
+            ledger,
+            state,
+            metrics,
             config,
-            client: Client::new(),
+        })
+    }
+
+    /// Refuse to run against RPCs that serve a different chain than the
+    /// selected network profile expects. Every endpoint is checked, not
+    /// just the preferred one, since any of them may be failed over to.
+    async fn verify_networks(&self) -> Result<()> {
+        let profile = self.config.network.profile();
+        let client = Client::new();
+        let retry = self.config.retry_policy();
+        let rpcs = |urls: &[String], chain: &'static str| -> Vec<RpcClient> {
+            urls.iter()
+                .map(|url| {
+                    RpcClient::new(client.clone(), url.clone())
+                        .with_retry(retry)
+                        .with_metrics(self.metrics.clone(), chain)
+                })
+                .collect()
+        };
+        let (celo, oasis, base) = (
+            rpcs(&self.config.celo_rpc, CELO),
+            rpcs(&self.config.oasis_rpc, OASIS),
+            rpcs(&self.config.base_rpc, BASE),
+        );
+
+        let (celo, oasis, base) = tokio::join!(
+            verify_each(&profile.celo, &celo),
+            verify_each(&profile.oasis, &oasis),
+            verify_each(&profile.base, &base),
+        );
+        let problems: Vec<String> = [celo, oasis, base].concat();
+        if !problems.is_empty() {
+            return Err(Error::Config(format!(
+                "RPC endpoints do not match the {} profile:\n  - {}",
+                self.config.network,
+                problems.join("\n  - ")
+            )));
+        }
+
+        info!(
+            "Verified chain IDs for {}: {} ({}), {} ({}), {} ({})",
+            self.config.network,
+            profile.celo.name,
+            profile.celo.chain_id,
+            profile.oasis.name,
+            profile.oasis.chain_id,
+            profile.base.name,
+            profile.base.chain_id
+        );
+        Ok(())
+    }
+
+    /// Check that every configured contract is deployed and wired to the
+    /// others as configured, listing every mismatch
+    async fn verify_deployment(&self) -> Result<()> {
+        let rpc = |urls: &[String]| {
+            RpcClient::failover(Client::new(), urls.to_vec()).with_retry(self.config.retry_policy())
+        };
+        let problems = deployment::verify_deployment(
+            &self.config,
+            &rpc(&self.config.celo_rpc),
+            &rpc(&self.config.oasis_rpc),
+            &rpc(&self.config.base_rpc),
+        )
+        .await;
+        if !problems.is_empty() {
+            return Err(Error::Config(format!(
+                "Deployed contracts do not match the configuration:\n  - {}",
+                problems.join("\n  - ")
+            )));
+        }
+        info!("Verified deployed contracts for {}", self.config.network);
+        Ok(())
+    }
+
+    /// Readiness checks over the same endpoints and signer the monitor uses.
+    /// Probe clients time out quickly so a hung node fails the probe instead
+    /// of hanging it.
+    fn readiness_probe(&self) -> Result<ReadinessProbe> {
+        let client = Client::builder()
+            .timeout(READINESS_RPC_TIMEOUT)
+            .build()
+            .map_err(|e| Error::Config(format!("readiness HTTP client: {}", e)))?;
+        let rpc = |url: &str| RpcClient::new(client.clone(), url);
+
+        let mut probe = ReadinessProbe::new(
+            self.metrics.clone(),
+            Duration::from_secs(self.config.check_interval_seconds.saturating_mul(2)),
+            U256::from(self.config.min_signer_balance_wei),
+        );
+        for (chain, urls) in [
+            (CELO, &self.config.celo_rpc),
+            (OASIS, &self.config.oasis_rpc),
+            (BASE, &self.config.base_rpc),
+        ] {
+            for url in urls {
+                probe = probe.endpoint(chain, rpc(url));
+            }
+        }
+        if let Some(signer) = self.data_store.signer() {
+            let oasis = RpcClient::failover(client.clone(), self.config.oasis_rpc.clone());
+            probe = probe.signer(signer, oasis);
+        }
+        Ok(probe)
+    }
+
+    /// Bridge fee quotes for the status server; `None` without a bridge
+    fn fee_quoter(&self) -> Option<FeeQuoter> {
+        let bridge = self.config.bridge_address?;
+        let base = RpcClient::failover(Client::new(), self.config.base_rpc.clone())
+            .with_metrics(self.metrics.clone(), BASE);
+        Some(FeeQuoter::new(
+            bridge,
+            base,
+            self.config.fee_margin_percent,
+            Duration::from_secs(self.config.fee_quote_ttl_seconds),
+        ))
+    }
+
+    /// Everything that has to survive a restart
+    fn snapshot(&self) -> MonitorState {
+        MonitorState {
+            checkpoints: [
+                Some((CELO.to_string(), self.indexer.next_block())),
+                Some((CELO_DEPOSITS.to_string(), self.vault_deposits.next_block())),
+                Some((CELO_VERIFICATIONS.to_string(), self.verification_watcher.next_block())),
+                self.splitter_deposits
+                    .as_ref()
+                    .map(|watcher| (BASE_DEPOSITS.to_string(), watcher.next_block())),
+                self.delivery_tracker
+                    .as_ref()
+                    .map(|tracker| (BASE_DISPATCHES.to_string(), tracker.next_dispatch_block())),
+                self.delivery_tracker
+                    .as_ref()
+                    .map(|tracker| (CELO_DELIVERIES.to_string(), tracker.next_delivery_block())),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+            children: self.indexer.children().clone(),
+            block_hashes: [(CELO.to_string(), self.indexer.block_hashes().clone())].into(),
+            growth: self.ledger.entries().collect(),
+            deposits: self.deposits.clone(),
+            verifications: self.verifications.clone(),
+            deliveries: self.deliveries.clone(),
+            pending_txs: self
+                .data_store
+                .sender()
+                .map(|sender| sender.pending())
+                .unwrap_or_default(),
         }
     }
 
-    /// Simulated: Fetch vault balance from Celo
-    /// In production: Use ethers-rs to call contract
-    async fn fetch_vault_balances(&self) -> Result<Vec<VaultBalance>, Box<dyn std::error::Error>> {
+    /// Discover new child accounts from ChildAccountCreated logs on Celo.
+    /// Children whose creation a reorg undid lose their growth history too.
+    async fn sync_children(&mut self) -> Result<()> {
+        let report = self.indexer.sync(&self.celo).await?;
+        if let Some(ancestor) = report.ancestor {
+            warn!(
+                "Celo reorganized above block {}; dropped {} children created since",
+                ancestor,
+                report.removed.len()
+            );
+            for child in &report.removed {
+                self.ledger.remove(*child);
+            }
+        }
+        info!(
+            "Indexed child accounts: {} new, {} known",
+            report.discovered,
+            self.indexer.children().len()
+        );
+        Ok(())
+    }
+
+    /// Fetch vault balances from Celo
+    /// Calls ScholarFiVault.getChildAccount for each indexed child, batched
+    /// through Multicall3 when available, otherwise one eth_call per child.
+    /// Children without an account, or whose read fails in a way specific
+    /// to that child, are skipped; anything else fails the whole fetch.
+    async fn fetch_vault_balances(&self) -> Result<Vec<VaultBalance>> {
         info!("Fetching vault balances from Celo...");
 
-        // DEMO: Return mock data
-        // In production: Query ScholarFiVault contract on Celo
-        Ok(vec![
-            VaultBalance {
-                child_address: "0x1234567890abcdef1234567890abcdef12345678".to_string(),
-                vault_amount: 1000000000000000000, // 1 ETH in wei
-                spending_amount: 500000000000000000, // 0.5 ETH
-                is_verified: false,
+        let children = self.indexer.children();
+        let mut balances = Vec::with_capacity(children.len());
+        let mut keep = |child: Address, balance: Result<Option<VaultBalance>>| {
+            match balance {
+                Ok(Some(balance)) => balances.push(balance),
+                Ok(None) => warn!("No vault account for child {}, skipping", child),
+                Err(e) if e.recovery() == Recovery::Skip => {
+                    warn!("Skipping child {}: {}", child, e)
+                }
+                Err(e) => return Err(e),
+            }
+            Ok(())
+        };
+
+        match &self.multicall {
+            Some(multicall) => {
+                let calls: Vec<_> = children
+                    .keys()
+                    .map(|&child| (self.vault, child_account_call(child)))
+                    .collect();
+                let results = multicall.call(&self.celo, &calls).await?;
+                for (&child, data) in children.keys().zip(results) {
+                    keep(child, data.and_then(|data| decode_child_account(&data)))?;
+                }
+            }
+            None => {
+                for &child in children.keys() {
+                    keep(child, self.fetch_vault_balance(child).await)?;
+                }
             }
-        ])
+        }
+
+        Ok(balances)
     }
 
-    /// Simulated: Check Aave APY
-    /// In production: Query Aave v3 pool on Celo
-    async fn check_aave_apy(&self) -> Result<f64, Box<dyn std::error::Error>> {
+    async fn fetch_vault_balance(&self, child: Address) -> Result<Option<VaultBalance>> {
+        // Growth is written from these balances, so don't trust one endpoint
+        let data = self.celo.quorum_call(self.vault, child_account_call(child)).await?;
+        decode_child_account(&data)
+    }
+
+    /// Reconcile deposits on Base and Celo against Oasis, and the vault's
+    /// native balance against what it owes children. Deposits are read
+    /// afresh from both chains rather than from the mirrored state, so the
+    /// report also catches deposits the monitor never saw. A child whose
+    /// Oasis profile cannot be read is reported as unavailable.
+    async fn reconciliation_report(&mut self) -> Result<ReconciliationReport> {
+        self.sync_children().await?;
+        let mut report = ReportBuilder::default();
+        for &child in self.indexer.children().keys() {
+            report.include(child);
+        }
+
+        let mut deposits = BTreeMap::new();
+        DepositWatcher::new(
+            CELO,
+            self.vault,
+            DepositSource::Vault,
+            self.config.vault_deployment_block,
+            self.config.log_page_size,
+            self.config.confirmation_depth,
+        )
+        .scan(&self.celo, &mut deposits)
+        .await?;
+        if let Some(splitter) = self.config.deposit_splitter_address {
+            DepositWatcher::new(
+                BASE,
+                splitter,
+                DepositSource::Splitter,
+                self.config.splitter_deployment_block,
+                self.config.log_page_size,
+                self.config.confirmation_depth,
+            )
+            .scan(&self.base, &mut deposits)
+            .await?;
+        }
+        for (key, deposit) in &deposits {
+            match key.chain.as_str() {
+                BASE => report.base_deposit(deposit.child, deposit.amount),
+                _ => report.celo_deposit(deposit.child, deposit.amount),
+            }
+        }
+
+        for balance in self.fetch_vault_balances().await? {
+            report.account(
+                balance.child_address,
+                U256::from(balance.vault_amount),
+                U256::from(balance.spending_amount),
+            );
+        }
+
+        for child in report.children() {
+            match self.data_store.total_deposited(child).await {
+                Ok(total) => report.oasis_total(child, Some(total)),
+                Err(Error::Revert {
+                    error: Some("ProfileNotFound"),
+                    ..
+                }) => report.oasis_total(child, None),
+                Err(e) => {
+                    warn!("Cannot read Oasis total deposited of {}: {}", child, e);
+                    report.oasis_unavailable(child);
+                }
+            }
+        }
+
+        let native_balance = self.celo.balance(self.vault).await?;
+        Ok(report.finish(self.vault, native_balance, unix_now()))
+    }
+
+    /// Check Aave APY
+    /// Reads the configured reserve from the Aave v3 PoolDataProvider on Celo
+    async fn check_aave_apy(&self) -> Result<ReserveRate> {
         info!("Checking Aave APY on Celo...");
 
-        // DEMO: Return simulated APY
-        // In production: Query Aave DataProvider contract
-        Ok(3.5) // 3.5% APY
+        self.aave.fetch_rate(&self.celo).await
     }
 
-    /// Simulated: Update Oasis Sapphire with growth data
-    /// In production: Send transaction to ChildDataStore
+    /// Update Oasis Sapphire with growth data
+    /// Sends ChildDataStore.updateVaultGrowth() and waits for the receipt.
+    /// Without a signing key this only logs what it would send.
     async fn update_oasis_growth(
         &self,
-        child_address: &str,
+        child_address: Address,
         vault_growth: u128
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<()> {
         info!(
             "Updating Oasis Sapphire: child={}, growth={}",
             child_address, vault_growth
         );
 
-        // DEMO: Log only
-        // In production: Call ChildDataStore.updateVaultGrowth()
-        info!("✓ Would update Oasis contract at: {}", self.config.child_data_store_address);
+        if self.data_store.signer().is_none() {
+            info!("✓ Would update Oasis contract at: {} (dry run, no ROFL_PRIVATE_KEY)", self.data_store.address());
+            self.metrics.growth_updates.with_label_values(&["dry_run"]).inc();
+            return Ok(());
+        }
+
+        let result = self
+            .data_store
+            .update_vault_growth(child_address, U256::from(vault_growth))
+            .await;
+        self.metrics.growth_updates.with_label_values(&[tx_outcome(&result)]).inc();
+        let receipt = result?;
+        info!(
+            "✓ Updated vault growth for {} in tx {}",
+            child_address, receipt.transaction_hash
+        );
+
+        Ok(())
+    }
+
+    /// Find new confirmed deposits on Celo and Base and record each one on
+    /// Oasis with `recordDeposit`. That call adds to `totalDeposited`, so
+    /// each transaction is committed against its deposit as soon as it is
+    /// broadcast, before waiting for the receipt: neither a crash nor a
+    /// failed receipt poll sends a second one while the first can still be
+    /// mined. A deposit is only sent again once its transaction is known to
+    /// have reverted or been dropped.
+    async fn mirror_deposits(&mut self, status: &mut CycleStatus) -> Result<()> {
+        if let Err(e) = self.vault_deposits.scan(&self.celo, &mut self.deposits).await {
+            status.handle("Failed to index Celo deposits", e)?;
+        }
+        if let Some(watcher) = &mut self.splitter_deposits {
+            if let Err(e) = watcher.scan(&self.base, &mut self.deposits).await {
+                status.handle("Failed to index Base deposits", e)?;
+            }
+        }
+
+        let unrecorded: Vec<DepositKey> = self
+            .deposits
+            .iter()
+            .filter(|(_, deposit)| deposit.recorded_tx.is_none())
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in unrecorded {
+            let deposit = self.deposits[&key].clone();
+            let context = format!("Failed to record {} deposit {}:{}", key.chain, key.tx_hash, key.log_index);
+            if self.data_store.signer().is_none() {
+                info!(
+                    "✓ Would record deposit of {} wei for {} (dry run, no ROFL_PRIVATE_KEY)",
+                    deposit.amount, deposit.child
+                );
+                self.metrics.deposits_recorded.with_label_values(&["dry_run"]).inc();
+                continue;
+            }
+
+            let submitted = self.data_store.record_deposit(deposit.child, deposit.amount).await;
+            if submitted.is_err() {
+                self.metrics.deposits_recorded.with_label_values(&[tx_outcome(&submitted)]).inc();
+            }
+            let hash = match submitted {
+                Ok(hash) => hash,
+                Err(e) => {
+                    status.handle(&context, e)?;
+                    continue;
+                }
+            };
+            self.set_recorded_tx(&key, Some(hash))?;
+
+            let confirmed = self.data_store.confirm_record_deposit(hash, deposit.child, deposit.amount).await;
+            self.metrics.deposits_recorded.with_label_values(&[tx_outcome(&confirmed)]).inc();
+            match confirmed {
+                Ok(_) => info!(
+                    "✓ Recorded deposit of {} wei for {} in tx {}",
+                    deposit.amount, deposit.child, hash
+                ),
+                // Mined but reverted: nothing was added, so it can be sent again
+                Err(e @ (Error::Revert { .. } | Error::TxFailed(_))) => {
+                    self.set_recorded_tx(&key, None)?;
+                    status.handle(&context, e)?;
+                }
+                // It may still be mined; never send a second one
+                Err(e) => {
+                    warn!("{}: waiting for {} failed ({}), leaving it pending", context, hash, e);
+                    status.retry = true;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Make deposits whose `recordDeposit` reverted or was dropped eligible
+    /// to be recorded again
+    fn forget_failed_deposits(&mut self, failed: &[B256]) -> Result<()> {
+        let mut forgotten = false;
+        for (key, deposit) in &mut self.deposits {
+            if deposit.recorded_tx.is_some_and(|hash| failed.contains(&hash)) {
+                warn!(
+                    "recordDeposit for {} deposit {}:{} did not take effect, recording it again",
+                    key.chain, key.tx_hash, key.log_index
+                );
+                deposit.recorded_tx = None;
+                forgotten = true;
+            }
+        }
+        if forgotten {
+            let snapshot = self.snapshot();
+            self.state.commit(&snapshot)?;
+        }
+        Ok(())
+    }
+
+    /// Set a deposit's `recordDeposit` transaction and commit it
+    fn set_recorded_tx(&mut self, key: &DepositKey, hash: Option<B256>) -> Result<()> {
+        if let Some(deposit) = self.deposits.get_mut(key) {
+            deposit.recorded_tx = hash;
+        }
+        let snapshot = self.snapshot();
+        self.state.commit(&snapshot)
+    }
+
+    /// Propagate age verification from Celo to Oasis: index new
+    /// verification events, reconcile with Oasis when due, then mark every
+    /// confirmed child that has not been marked yet
+    async fn propagate_age_verification(&mut self, status: &mut CycleStatus) -> Result<()> {
+        if let Err(e) = self.verification_watcher.scan(&self.celo, &mut self.verifications).await {
+            status.handle("Failed to index age verification", e)?;
+        }
+
+        if self.age_reconcile_due() {
+            match self.reconcile_age_verification().await {
+                Ok(()) => self.age_reconciled_at = Some(Instant::now()),
+                Err(e) => status.handle("Failed to reconcile age verification", e)?,
+            }
+        }
+
+        self.mark_age_verified(status).await
+    }
+
+    /// Follow bridged deposits through Hyperlane: match dispatches on Base
+    /// with deliveries on Celo, record each delivery's latency, and alert
+    /// once on every message still undelivered after the timeout
+    async fn track_deliveries(&mut self, status: &mut CycleStatus) -> Result<()> {
+        let Some(tracker) = &mut self.delivery_tracker else {
+            return Ok(());
+        };
+        let mut completed = Vec::new();
+        match tracker.scan_dispatches(&self.base, &mut self.deliveries).await {
+            Ok(ids) => completed.extend(ids),
+            Err(e) => status.handle("Failed to index Hyperlane dispatches", e)?,
+        }
+        match tracker.scan_deliveries(&self.celo, &mut self.deliveries).await {
+            Ok(ids) => completed.extend(ids),
+            Err(e) => status.handle("Failed to index Hyperlane deliveries", e)?,
+        }
+        for id in completed {
+            if let Some(latency) = self.deliveries[&id].latency() {
+                info!("Hyperlane message {} delivered after {}s", id, latency);
+                self.metrics.delivery_latency.observe(latency as f64);
+            }
+        }
+
+        // Dry-run each overdue message so a delivery that would revert is
+        // reported with its fix. Relayers attach no value, so a message
+        // still in flight would always show up as ZeroAmount; only once it
+        // is overdue is that worth an alert.
+        let now = unix_now();
+        let timeout = self.config.delivery_timeout_seconds;
+        let pending: BTreeMap<B256, HyperlaneMessage> = self
+            .deliveries
+            .iter()
+            .filter(|(_, delivery)| delivery.is_overdue(now, timeout))
+            .filter_map(|(id, delivery)| Some((*id, delivery.message.clone()?)))
+            .collect();
+        self.rejections.retain(|id, _| pending.contains_key(id));
+        for (id, message) in &pending {
+            let revert = match tracker.simulate(&self.celo, message).await {
+                Ok(revert) => revert,
+                Err(e) => {
+                    status.handle("Failed to simulate Hyperlane delivery", e)?;
+                    continue;
+                }
+            };
+            let Some(revert) = revert else {
+                self.rejections.remove(id);
+                continue;
+            };
+            if self.rejections.get(id) != Some(&revert) {
+                error!(
+                    target: rpc::ALERT_TARGET,
+                    "Hyperlane message {} would revert in ScholarFiVault.handle with {}: {}",
+                    id,
+                    revert,
+                    revert.remediation(message)
+                );
+                self.rejections.insert(*id, revert);
+            }
+        }
+        self.metrics.unprocessable.set(self.rejections.len() as i64);
+
+        let (mut undelivered, mut overdue) = (0, 0);
+        for (id, delivery) in &mut self.deliveries {
+            let (Some(dispatched), None) = (delivery.dispatched, delivery.delivered) else {
+                continue;
+            };
+            undelivered += 1;
+            if !delivery.is_overdue(now, timeout) {
+                continue;
+            }
+            overdue += 1;
+            if !delivery.alerted {
+                error!(
+                    target: rpc::ALERT_TARGET,
+                    "Hyperlane message {} (deposit for {}) dispatched at Base block {} still undelivered after {}s",
+                    id,
+                    delivery.child.map_or("unknown child".to_string(), |child| child.to_string()),
+                    dispatched.block,
+                    now.saturating_sub(dispatched.timestamp)
+                );
+                delivery.alerted = true;
+            }
+        }
+        self.metrics.undelivered.set(undelivered);
+        self.metrics.overdue.set(overdue);
+        Ok(())
+    }
+
+    fn age_reconcile_due(&self) -> bool {
+        let interval = Duration::from_secs(self.config.age_reconcile_interval_seconds);
+        !interval.is_zero()
+            && self.config.age_verifier_address.is_some()
+            && self.age_reconciled_at.is_none_or(|at| at.elapsed() >= interval)
+    }
+
+    /// Compare `isChildVerified` on Celo with `ageVerifiedOnCelo` on Oasis
+    /// for every known child, and queue any child Oasis is missing to be
+    /// marked again. Drift is logged under the alert target.
+    async fn reconcile_age_verification(&mut self) -> Result<()> {
+        let Some(verifier) = self.config.age_verifier_address else {
+            return Ok(());
+        };
+        let head = self.celo.block_number().await?;
+        let children: Vec<Address> = self.indexer.children().keys().copied().collect();
+
+        let (mut drifted, mut unreadable) = (0, 0);
+        for child in children {
+            let call = ScholarFiAgeVerifier::isChildVerifiedCall { childAddress: child };
+            let data = self.celo.quorum_call(verifier, call.abi_encode().into()).await?;
+            if !ScholarFiAgeVerifier::isChildVerifiedCall::abi_decode_returns(&data)? {
+                continue;
+            }
+            match self.data_store.age_verified(child).await {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(e) if e.recovery() == Recovery::Skip => {
+                    warn!("Cannot read Oasis profile of {}: {}", child, e);
+                    unreadable += 1;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            }
+            error!(
+                target: rpc::ALERT_TARGET,
+                "Child {} is age-verified on Celo but not on Oasis; marking it again", child
+            );
+            drifted += 1;
+            self.verifications
+                .entry(child)
+                .and_modify(|verification| verification.marked_tx = None)
+                .or_insert(Verification::seen_at(head));
+        }
+
+        info!(
+            "Reconciled age verification: {} children drifted, {} unreadable on Oasis",
+            drifted, unreadable
+        );
+        Ok(())
+    }
+
+    /// Send `markAgeVerified` for every confirmed verification Oasis has
+    /// not been told about. The call is idempotent, so a crash before the
+    /// state is committed only costs a repeated transaction.
+    async fn mark_age_verified(&mut self, status: &mut CycleStatus) -> Result<()> {
+        let unmarked: Vec<Address> = self
+            .verifications
+            .iter()
+            .filter(|(_, verification)| verification.marked_tx.is_none())
+            .map(|(&child, _)| child)
+            .collect();
+        if unmarked.is_empty() {
+            return Ok(());
+        }
+        let head = match self.celo.block_number().await {
+            Ok(head) => head,
+            Err(e) => return status.handle("Failed to read the Celo head", e),
+        };
+
+        for child in unmarked {
+            if !self.verification_watcher.is_confirmed(self.verifications[&child].block, head) {
+                continue;
+            }
+            let hash = match self.send_age_verified(child).await {
+                Ok(Some(hash)) => hash,
+                Ok(None) => continue,
+                Err(Error::TxTimeout(hash)) => {
+                    warn!("Receipt for markAgeVerified({}) in {} timed out, leaving it pending", child, hash);
+                    status.retry = true;
+                    hash
+                }
+                Err(e) => {
+                    status.handle(&format!("Failed to mark {} age-verified", child), e)?;
+                    continue;
+                }
+            };
+            if let Some(verification) = self.verifications.get_mut(&child) {
+                verification.marked_tx = Some(hash);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send `markAgeVerified` for one child. Returns the transaction hash,
+    /// or `None` in dry-run mode.
+    async fn send_age_verified(&self, child: Address) -> Result<Option<B256>> {
+        if self.data_store.signer().is_none() {
+            info!("✓ Would mark {} age-verified (dry run, no ROFL_PRIVATE_KEY)", child);
+            self.metrics.age_verifications.with_label_values(&["dry_run"]).inc();
+            return Ok(None);
+        }
+
+        let result = self.data_store.mark_age_verified(child).await;
+        self.metrics.age_verifications.with_label_values(&[tx_outcome(&result)]).inc();
+        let receipt = result?;
+        info!("✓ Marked {} age-verified in tx {}", child, receipt.transaction_hash);
+
+        Ok(Some(receipt.transaction_hash))
+    }
+
+    /// Accrue growth for one child since its last update and write the
+    /// running total to Oasis. Children seen for the first time are seeded
+    /// from the total already stored on-chain and start accruing from `now`.
+    async fn accrue_growth(
+        &mut self,
+        vault: &VaultBalance,
+        rate_per_second: Ray,
+        now: u64,
+    ) -> Result<()> {
+        let child = vault.child_address;
+
+        let accrual = match self.ledger.accrue(child, vault.vault_amount, rate_per_second, now) {
+            Ok(accrual) => accrual,
+            Err(AccrualError::Untracked(_)) => {
+                let stored = self.data_store.vault_growth(child).await?;
+                info!("Tracking growth for {} from stored total {}", child, stored);
+                self.ledger.open(child, stored.try_into()?, now);
+                return Ok(());
+            }
+            Err(e @ AccrualError::Overflow(_)) => return Err(Error::Overflow(e.to_string())),
+        };
+
+        if accrual.accrued == 0 {
+            // An empty vault earns nothing for this period; a funded one just
+            // hasn't accrued a whole wei yet, so keep its period open
+            if vault.vault_amount == 0 {
+                self.ledger.commit(&accrual);
+            }
+            return Ok(());
+        }
+
+        info!(
+            "Accrued {} wei for {} over {}s (total {})",
+            accrual.accrued, child, accrual.elapsed, accrual.total
+        );
+        self.update_oasis_growth(child, accrual.total).await?;
+        self.ledger.commit(&accrual);
 
         Ok(())
     }
 
     /// Main monitoring loop
-    async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
+    async fn run(&mut self) -> Result<()> {
         info!("========================================");
         info!("Scholar-Fi ROFL Monitor Started");
         info!("========================================");
-        info!("Celo RPC: {}", self.config.celo_rpc);
-        info!("Oasis RPC: {}", self.config.oasis_rpc);
+        info!("Network: {}", self.config.network);
+        info!(
+            "Celo RPC: {} (quorum {})",
+            self.config.celo_rpc.join(", "),
+            self.config.celo_quorum
+        );
+        info!("Oasis RPC: {}", self.config.oasis_rpc.join(", "));
+        info!("Base RPC: {}", self.config.base_rpc.join(", "));
         info!("Vault Address: {}", self.config.scholar_fi_vault_address);
         info!("Data Store: {}", self.config.child_data_store_address);
+        match self.data_store.signer() {
+            Some(signer) => info!("Signer: {}", signer),
+            None => warn!("No ROFL_PRIVATE_KEY set, Oasis updates run dry"),
+        }
         info!("Check Interval: {}s", self.config.check_interval_seconds);
+        match &self.config.celo_ws_url {
+            Some(_) => info!("Vault events: subscribed over websocket"),
+            None => info!("Vault events: polling only (no CELO_WS_URL)"),
+        }
+        info!(
+            "State: {} ({} known children, resuming at block {})",
+            self.config.state_path,
+            self.indexer.children().len(),
+            self.indexer.next_block()
+        );
         info!("========================================");
 
-        let mut interval = time::interval(Duration::from_secs(self.config.check_interval_seconds));
+        let interval = Duration::from_secs(self.config.check_interval_seconds);
+        let mut retries = 0;
+        let mut events = self.subscribe();
 
+        // Retryable failures bring the next cycle forward with exponential
+        // backoff (capped at the regular interval); fatal ones stop the monitor.
+        // Vault events are handled as they arrive in between.
         loop {
-            interval.tick().await;
-
-            info!("=== Monitoring Cycle Started ===");
-
-            // 1. Fetch vault balances from Celo
-            match self.fetch_vault_balances().await {
-                Ok(vaults) => {
-                    info!("Found {} active vaults", vaults.len());
-
-                    // 2. Check Aave APY
-                    match self.check_aave_apy().await {
-                        Ok(apy) => {
-                            info!("Current Aave APY: {:.2}%", apy);
-
-                            // 3. Analyze rebalancing opportunities
-                            if apy < 2.0 {
-                                info!("⚠️  APY below threshold (2.0%). Consider rebalancing!");
-                            } else {
-                                info!("✓ APY is healthy");
-                            }
-
-                            // 4. Update Oasis with vault growth
-                            for vault in vaults {
-                                // Simulate growth calculation
-                                let simulated_growth = (vault.vault_amount as f64 * apy / 100.0 / 365.0) as u128;
-
-                                if let Err(e) = self.update_oasis_growth(&vault.child_address, simulated_growth).await {
-                                    error!("Failed to update Oasis: {}", e);
-                                }
-                            }
+            let delay = if self.cycle().await? {
+                retries = 0;
+                interval
+            } else {
+                retries += 1;
+                let delay = retry_delay(retries, interval);
+                warn!("Cycle hit transient errors, retrying in {}s", delay.as_secs());
+                delay
+            };
+            self.wait(delay, &mut events).await?;
+        }
+    }
+
+    /// Live vault events, if a websocket endpoint is configured
+    fn subscribe(&self) -> Option<mpsc::Receiver<VaultEvent>> {
+        let url = self.config.celo_ws_url.clone()?;
+        let backfill = RpcClient::failover(Client::new(), self.config.celo_rpc.clone())
+            .with_retry(self.config.retry_policy())
+            .with_metrics(self.metrics.clone(), CELO);
+        Some(VaultSubscription::new(url, self.vault, backfill, self.config.log_page_size).spawn())
+    }
+
+    /// Sleep for `delay`, handling vault events as they arrive
+    async fn wait(&mut self, delay: Duration, events: &mut Option<mpsc::Receiver<VaultEvent>>) -> Result<()> {
+        let deadline = time::sleep(delay);
+        tokio::pin!(deadline);
+
+        while let Some(receiver) = events {
+            tokio::select! {
+                _ = &mut deadline => return Ok(()),
+                event = receiver.recv() => {
+                    let Some(event) = event else {
+                        warn!("Vault subscription stopped, falling back to polling");
+                        *events = None;
+                        break;
+                    };
+                    // Handle a burst of events in one go
+                    let mut children = BTreeMap::new();
+                    let mut next = Some(event);
+                    while let Some(event) = next {
+                        info!("Vault event {:?} for {} at block {}", event.kind, event.child, event.block);
+                        self.metrics.vault_events.with_label_values(&[event.kind.name()]).inc();
+                        children.entry(event.child).or_insert(event.block);
+                        if matches!(event.kind, VaultEventKind::Unlock | VaultEventKind::AgeVerified) {
+                            self.verifications
+                                .entry(event.child)
+                                .or_insert(Verification::seen_at(event.block));
                         }
-                        Err(e) => error!("Failed to check Aave APY: {}", e),
+                        next = receiver.try_recv().ok();
                     }
+                    self.process_children(&children).await?;
                 }
-                Err(e) => error!("Failed to fetch vault balances: {}", e),
             }
+        }
+
+        deadline.await;
+        Ok(())
+    }
+
+    /// Bring children named in vault events up to date between cycles:
+    /// index any new ones, mark confirmed age verifications, re-read their
+    /// accounts and accrue their growth.
+    /// Transient failures are left for the next cycle; fatal ones stop the
+    /// monitor as they would in a cycle.
+    async fn process_children(&mut self, children: &BTreeMap<Address, u64>) -> Result<()> {
+        let mut status = CycleStatus::default();
+        let outcome = self.process_children_steps(children, &mut status).await;
+
+        let snapshot = self.snapshot();
+        if let Err(e) = self.state.commit(&snapshot) {
+            error!("Failed to persist monitor state: {}", e);
+            status.fatal.get_or_insert(e);
+        }
+        if let Err(e) = outcome {
+            status.fatal.get_or_insert(e);
+        }
 
-            info!("=== Monitoring Cycle Complete ===\n");
+        match status.fatal {
+            Some(e) => Err(e),
+            None => Ok(()),
         }
     }
+
+    async fn process_children_steps(
+        &mut self,
+        children: &BTreeMap<Address, u64>,
+        status: &mut CycleStatus,
+    ) -> Result<()> {
+        for (&child, &block) in children {
+            if self.indexer.insert(child, block) {
+                info!("Discovered child {} from a vault event", child);
+            }
+        }
+
+        // Verifications from earlier events that are confirmed by now
+        self.mark_age_verified(status).await?;
+
+        let rate = match self.check_aave_apy().await {
+            Ok(rate) => rate,
+            Err(e) => return status.handle("Failed to check Aave APY", e),
+        };
+
+        let now = unix_now();
+        for &child in children.keys() {
+            let vault = match self.fetch_vault_balance(child).await {
+                Ok(Some(vault)) => vault,
+                Ok(None) => continue,
+                Err(e) => {
+                    status.handle(&format!("Failed to read vault for {}", child), e)?;
+                    continue;
+                }
+            };
+            if let Err(e) = self.accrue_growth(&vault, rate.rate_per_second(), now).await {
+                status.handle(&format!("Failed to update Oasis for {}", child), e)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// One monitoring cycle. Returns `Ok(true)` if every step succeeded,
+    /// `Ok(false)` if a step failed with a retryable error, and the error
+    /// itself if it was fatal. Errors limited to one child are logged and
+    /// skipped. State is committed either way.
+    async fn cycle(&mut self) -> Result<bool> {
+        let started = Instant::now();
+        let mut status = CycleStatus::default();
+
+        info!("=== Monitoring Cycle Started ===");
+
+        let outcome = self.cycle_steps(&mut status).await;
+
+        // Persist the whole cycle at once
+        let snapshot = self.snapshot();
+        if let Err(e) = self.state.commit(&snapshot) {
+            error!("Failed to persist monitor state: {}", e);
+            status.fatal.get_or_insert(e);
+        }
+        if let Err(e) = outcome {
+            status.fatal.get_or_insert(e);
+        }
+
+        let ok = status.fatal.is_none() && !status.retry;
+        self.metrics.cycle_duration.observe(started.elapsed().as_secs_f64());
+        self.metrics
+            .cycles
+            .with_label_values(&[if ok { "success" } else { "failure" }])
+            .inc();
+        if ok {
+            self.metrics.last_success.set(unix_now() as i64);
+        }
+
+        info!("=== Monitoring Cycle Complete ===\n");
+        match status.fatal {
+            Some(e) => Err(e),
+            None => Ok(ok),
+        }
+    }
+
+    async fn cycle_steps(&mut self, status: &mut CycleStatus) -> Result<()> {
+        match self.data_store.reconcile_pending().await {
+            Ok(failed) => self.forget_failed_deposits(&failed)?,
+            Err(e) => status.handle("Failed to check pending transactions", e)?,
+        }
+
+        // Keep indexing where we left off; a failed sync still leaves
+        // previously discovered children to monitor
+        if let Err(e) = self.sync_children().await {
+            status.handle("Failed to index child accounts", e)?;
+        }
+
+        self.mirror_deposits(status).await?;
+        self.propagate_age_verification(status).await?;
+        self.track_deliveries(status).await?;
+
+        // 1. Fetch vault balances from Celo
+        let vaults = match self.fetch_vault_balances().await {
+            Ok(vaults) => vaults,
+            Err(e) => return status.handle("Failed to fetch vault balances", e),
+        };
+        info!("Found {} active vaults", vaults.len());
+        self.record_balances(&vaults);
+
+        // 2. Check Aave APY
+        let rate = match self.check_aave_apy().await {
+            Ok(rate) => rate,
+            Err(e) => return status.handle("Failed to check Aave APY", e),
+        };
+        info!(
+            "Current Aave APY: {}% (utilization {}%)",
+            percent(rate.apy),
+            percent(rate.utilization)
+        );
+        self.metrics.apy.set(rate.apy.to_f64());
+
+        // 3. Analyze rebalancing opportunities
+        if rate.apy < Ray::from_bps(APY_THRESHOLD_BPS) {
+            info!("⚠️  APY below threshold (2.0%). Consider rebalancing!");
+        } else {
+            info!("✓ APY is healthy");
+        }
+
+        // 4. Update Oasis with cumulative vault growth,
+        // compounding the supply rate every second
+        let now = unix_now();
+        for vault in vaults {
+            if let Err(e) = self.accrue_growth(&vault, rate.rate_per_second(), now).await {
+                status.handle(&format!("Failed to update Oasis for {}", vault.child_address), e)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn record_balances(&self, vaults: &[VaultBalance]) {
+        let vault_total = vaults.iter().fold(0u128, |sum, v| sum.saturating_add(v.vault_amount));
+        let spending_total = vaults.iter().fold(0u128, |sum, v| sum.saturating_add(v.spending_amount));
+        self.metrics.vaults.set(vaults.len() as i64);
+        self.metrics.vault_balance.set(vault_total as f64);
+        self.metrics.spending_balance.set(spending_total as f64);
+    }
+}
+
+/// What went wrong during one cycle
+#[derive(Default)]
+struct CycleStatus {
+    /// A step failed transiently and the cycle should be retried soon
+    retry: bool,
+    fatal: Option<Error>,
+}
+
+impl CycleStatus {
+    /// Log `e` and decide how the cycle continues: retryable and per-item
+    /// errors let it go on, fatal ones are handed back to stop it
+    fn handle(&mut self, context: &str, e: Error) -> Result<()> {
+        match e.recovery() {
+            Recovery::Retry => {
+                error!("{}: {} (will retry)", context, e);
+                self.retry = true;
+                Ok(())
+            }
+            Recovery::Skip => {
+                warn!("{}: {} (skipped)", context, e);
+                Ok(())
+            }
+            Recovery::Abort => {
+                error!("{}: {} (fatal)", context, e);
+                Err(e)
+            }
+        }
+    }
+}
+
+fn child_account_call(child: Address) -> Bytes {
+    ScholarFiVault::getChildAccountCall { _child: child }.abi_encode().into()
+}
+
+/// `getChildAccount` return data; unset mapping entries decode as all
+/// zeroes and mean the child has no account
+fn decode_child_account(data: &[u8]) -> Result<Option<VaultBalance>> {
+    let account = ScholarFiVault::getChildAccountCall::abi_decode_returns(data)?;
+    if account.childWallet == Address::ZERO {
+        return Ok(None);
+    }
+
+    Ok(Some(VaultBalance {
+        child_address: account.childWallet,
+        vault_amount: account.vaultBalance.try_into()?,
+        spending_amount: account.spendingBalance.try_into()?,
+        is_verified: account.isVerified,
+    }))
+}
+
+/// Check every endpoint in `rpcs` against `chain`, collecting the problems
+async fn verify_each(chain: &ChainProfile, rpcs: &[RpcClient]) -> Vec<String> {
+    let mut problems = Vec::new();
+    for rpc in rpcs {
+        if let Err(problem) = chain.verify(rpc).await {
+            problems.push(problem);
+        }
+    }
+    problems
+}
+
+/// Metric label for how a transaction went
+fn tx_outcome<T>(result: &Result<T>) -> &'static str {
+    match result {
+        Ok(_) => "success",
+        Err(Error::Revert { .. }) => "reverted",
+        Err(Error::TxFailed(_)) => "failed",
+        Err(Error::TxTimeout(_)) => "timeout",
+        Err(_) => "error",
+    }
+}
+
+/// First retry after `RETRY_BASE_DELAY`, doubling each time, never longer
+/// than the regular check interval
+fn retry_delay(retries: u32, interval: Duration) -> Duration {
+    RETRY_BASE_DELAY
+        .saturating_mul(1 << retries.saturating_sub(1).min(16))
+        .min(interval)
+}
+
+/// A ray fraction as a percentage, e.g. 0.035 -> 3.5
+fn percent(rate: Ray) -> Ray {
+    rate.checked_mul_int(U256::from(100u8)).unwrap_or(rate)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+const USAGE: &str =
+    "usage: scholar-fi-rofl [--config <path>] [--network testnet|mainnet|local] [--report json|table]";
+
+/// Command line flags
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    config: Option<PathBuf>,
+    network: Option<Network>,
+    /// Print a reconciliation report and exit instead of monitoring
+    report: Option<ReportFormat>,
+}
+
+impl Args {
+    /// Accepts `--flag value` and `--flag=value`
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut parsed = Args::default();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| Error::Config(format!("{} requires a value\n{}", flag, USAGE)))
+            };
+            match flag.as_str() {
+                "--config" => parsed.config = Some(value()?.into()),
+                "--network" => parsed.network = Some(value()?.parse().map_err(Error::Config)?),
+                "--report" => parsed.report = Some(value()?.parse()?),
+                _ => return Err(Error::Config(format!("unknown argument {:?}\n{}", flag, USAGE))),
+            }
+        }
+        Ok(parsed)
+    }
 }
 
 #[tokio::main]
@@ -176,12 +1376,853 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logger
     env_logger::init();
 
-    // Load config
-    let config = MonitoringConfig::default();
+    // Load config: network profile, then the TOML file from --config or
+    // ROFL_CONFIG, then env
+    let args = Args::parse(std::env::args().skip(1))?;
+    let path = args.config.or_else(|| std::env::var_os("ROFL_CONFIG").map(PathBuf::from));
+    let config = match MonitoringConfig::load(path.as_deref(), args.network) {
+        Ok(config) => config,
+        Err(errors) => {
+            eprint!("{}", errors);
+            std::process::exit(2);
+        }
+    };
 
     // Create and run monitor
-    let monitor = RoflMonitor::new(config);
+    let mut monitor = RoflMonitor::new(config)?;
+    monitor.verify_networks().await?;
+    monitor.verify_deployment().await?;
+    if let Some(format) = args.report {
+        let report = monitor.reconciliation_report().await?;
+        match format {
+            ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            ReportFormat::Table => print!("{}", report),
+        }
+        // Non-zero so cron jobs and CI can alert on drift
+        std::process::exit(if report.is_consistent() { 0 } else { 1 });
+    }
+    let mut server = StatusServer::new(monitor.metrics.clone(), monitor.readiness_probe()?);
+    if let Some(quoter) = monitor.fee_quoter() {
+        server = server.fees(quoter, &monitor.config.fee_cors_origin);
+    }
+    server.spawn(monitor.config.http_addr)?;
     monitor.run().await?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contracts::IMulticall3;
+    use crate::mock_rpc::{reverted, MockChain, MockRpc};
+    use alloy_primitives::{address, B256, U256, U64};
+    use alloy_sol_types::{SolError, SolEvent, SolValue};
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+    const VAULT: Address = address!("00000000000000000000000000000000000000aa");
+    const PARENT: Address = address!("00000000000000000000000000000000000000bb");
+    const ALICE: Address = address!("0000000000000000000000000000000000000001");
+    const BOB: Address = address!("0000000000000000000000000000000000000002");
+
+    fn test_config(celo_rpc: &str) -> MonitoringConfig {
+        MonitoringConfig {
+            network: Network::Testnet,
+            celo_rpc: vec![celo_rpc.to_string()],
+            oasis_rpc: vec!["http://127.0.0.1:1".to_string()],
+            base_rpc: vec!["http://127.0.0.1:1".to_string()],
+            celo_quorum: 1,
+            celo_ws_url: None,
+            scholar_fi_vault_address: VAULT,
+            child_data_store_address: Address::ZERO,
+            aave_data_provider_address: Address::ZERO,
+            aave_asset_address: Address::ZERO,
+            vault_deployment_block: 0,
+            age_verifier_address: None,
+            age_reconcile_interval_seconds: 0,
+            deposit_splitter_address: None,
+            splitter_deployment_block: 0,
+            bridge_address: None,
+            bridge_deployment_block: 0,
+            base_mailbox_address: None,
+            celo_mailbox_address: None,
+            delivery_timeout_seconds: 1800,
+            fee_margin_percent: 10,
+            fee_quote_ttl_seconds: 30,
+            fee_cors_origin: "*".to_string(),
+            log_page_size: 1000,
+            confirmation_depth: 4,
+            multicall_address: None,
+            multicall_batch_size: 0,
+            rofl_private_key: None,
+            tx_timeout_seconds: 5,
+            state_path: ":memory:".to_string(),
+            check_interval_seconds: 60,
+            http_addr: ([127, 0, 0, 1], 0).into(),
+            min_signer_balance_wei: 0,
+            // One attempt per call keeps the call counts below exact
+            rpc_max_attempts: 1,
+            rpc_timeout_seconds: 5,
+            rpc_backoff_ms: 10,
+            rpc_max_backoff_ms: 10,
+            rpc_jitter_percent: 0,
+        }
+    }
+
+    /// Canned ScholarFiVault: ALICE and BOB were created (BOB's account is
+    /// since gone), ALICE has an account, everyone else reads as empty
+    fn vault_handler(method: &str, params: &Value) -> Result<Value, rpc::JsonRpcError> {
+        match method {
+            "eth_blockNumber" => return Ok(json!("0x10")),
+            "eth_getBlockByNumber" => {
+                let number: U64 = serde_json::from_value(params[0].clone()).unwrap();
+                let hash = |n: u64| B256::left_padding_from(&n.to_be_bytes());
+                let n = number.to::<u64>();
+                return Ok(json!({ "number": number, "hash": hash(n), "parentHash": hash(n.saturating_sub(1)), "timestamp": number }));
+            }
+            // No deposits, only ChildAccountCreated
+            "eth_getLogs" if params[0]["topics"][0][0] != json!(ScholarFiVault::ChildAccountCreated::SIGNATURE_HASH) => {
+                return Ok(json!([]));
+            }
+            "eth_getLogs" => {
+                let logs: Vec<Value> = [ALICE, BOB]
+                    .into_iter()
+                    .map(|child| {
+                        let event = ScholarFiVault::ChildAccountCreated {
+                            child,
+                            parent: PARENT,
+                            timestamp: U256::from(1_700_000_000u64),
+                        };
+                        let data = event.encode_log_data();
+                        json!({
+                            "address": VAULT,
+                            "topics": data.topics(),
+                            "data": data.data,
+                            "blockNumber": "0x5",
+                        })
+                    })
+                    .collect();
+                return Ok(Value::Array(logs));
+            }
+            _ => assert_eq!(method, "eth_call"),
+        }
+        assert_eq!(params[0]["to"].as_str().unwrap().parse::<Address>().unwrap(), VAULT);
+
+        let data: Bytes = params[0]["data"].as_str().unwrap().parse().unwrap();
+        let call = ScholarFiVault::getChildAccountCall::abi_decode(&data).unwrap();
+
+        let account = if call._child == ALICE {
+            ScholarFiVault::getChildAccountReturn {
+                childWallet: ALICE,
+                parentWallet: PARENT,
+                vaultBalance: U256::from(3_000_000_000_000_000_000u128),
+                spendingBalance: U256::from(7_000_000_000_000_000_000u128),
+                isVerified: true,
+                createdAt: U256::from(1_700_000_000u64),
+            }
+        } else {
+            ScholarFiVault::getChildAccountReturn {
+                childWallet: Address::ZERO,
+                parentWallet: Address::ZERO,
+                vaultBalance: U256::ZERO,
+                spendingBalance: U256::ZERO,
+                isVerified: false,
+                createdAt: U256::ZERO,
+            }
+        };
+
+        let encoded = ScholarFiVault::getChildAccountCall::abi_encode_returns(&account);
+        Ok(Value::String(Bytes::from(encoded).to_string()))
+    }
+
+    /// Canned Sapphire node that accepts every transaction and mines it
+    /// successfully in block 0x10
+    fn oasis_handler(method: &str, params: &Value) -> Result<Value, rpc::JsonRpcError> {
+        match method {
+            "eth_estimateGas" => Ok(json!("0x7530")),
+            "eth_chainId" => Ok(json!("0x5aff")),
+            "eth_getTransactionCount" => Ok(json!("0x0")),
+            "eth_gasPrice" => Ok(json!("0x174876e800")),
+            "eth_sendRawTransaction" => {
+                let raw: Bytes = params[0].as_str().unwrap().parse().unwrap();
+                Ok(json!(alloy_primitives::keccak256(&raw)))
+            }
+            "eth_getTransactionReceipt" => Ok(json!({
+                "transactionHash": params[0],
+                "blockNumber": "0x10",
+                "gasUsed": "0x7530",
+                "status": "0x1",
+            })),
+            other => panic!("unexpected method {other}"),
+        }
+    }
+
+    /// ChildDataStore at the zero address on `oasis`, with a signer
+    fn oasis_store(oasis: &MockRpc) -> DataStore {
+        let signer = Signer::from_hex("0x4646464646464646464646464646464646464646464646464646464646464646").unwrap();
+        DataStore::new(
+            Address::ZERO,
+            RpcClient::new(reqwest::Client::new(), oasis.url()),
+            Some(TxSender::new(signer, Duration::from_secs(5), Duration::from_millis(10))),
+        )
+    }
+
+    /// A 1000 wei `FundsDeposited` for ALICE in block 3 of `chain`
+    fn add_vault_deposit(chain: &MockChain, log_index: u64) {
+        let data = ScholarFiVault::FundsDeposited {
+            child: ALICE,
+            parent: PARENT,
+            totalAmount: U256::from(1_000u64),
+            vaultAmount: U256::from(300u64),
+            spendingAmount: U256::from(700u64),
+        }
+        .encode_log_data();
+        chain.add_log(
+            3,
+            json!({
+                "address": VAULT,
+                "topics": data.topics(),
+                "data": data.data,
+                "transactionHash": B256::repeat_byte(0x01),
+                "logIndex": U64::from(log_index),
+            }),
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_vault_balances_decodes_child_accounts() {
+        let node = MockRpc::start(vault_handler).await;
+        let mut monitor = RoflMonitor::new(test_config(node.url())).unwrap();
+
+        monitor.sync_children().await.unwrap();
+        let balances = monitor.fetch_vault_balances().await.unwrap();
+
+        let eth_calls = node.calls().iter().filter(|(m, _)| m == "eth_call").count();
+        assert_eq!(eth_calls, 2);
+        assert_eq!(balances.len(), 1, "child without an account is skipped");
+        assert_eq!(balances[0].child_address, ALICE);
+        assert_eq!(balances[0].vault_amount, 3_000_000_000_000_000_000);
+        assert_eq!(balances[0].spending_amount, 7_000_000_000_000_000_000);
+        assert!(balances[0].is_verified);
+    }
+
+    #[tokio::test]
+    async fn fetch_vault_balances_surfaces_rpc_errors() {
+        let node = MockRpc::start(|method, params| match method {
+            "eth_call" => Err(rpc::JsonRpcError {
+                code: -32005,
+                message: "limit exceeded".to_string(),
+                data: None,
+            }),
+            _ => vault_handler(method, params),
+        })
+        .await;
+        let mut monitor = RoflMonitor::new(test_config(node.url())).unwrap();
+        monitor.sync_children().await.unwrap();
+
+        let err = monitor.fetch_vault_balances().await.unwrap_err();
+        assert!(err.to_string().contains("limit exceeded"));
+        assert_eq!(err.recovery(), Recovery::Retry);
+    }
+
+    #[tokio::test]
+    async fn fetch_vault_balances_skips_reverting_child() {
+        let node = MockRpc::start(|method, params| {
+            let data: Bytes = params[0]["data"].as_str().unwrap_or("0x").parse().unwrap();
+            match ScholarFiVault::getChildAccountCall::abi_decode(&data) {
+                Ok(call) if method == "eth_call" && call._child == ALICE => Err(reverted([0xde, 0xad, 0xbe, 0xef])),
+                _ => vault_handler(method, params),
+            }
+        })
+        .await;
+        let mut monitor = RoflMonitor::new(test_config(node.url())).unwrap();
+        monitor.sync_children().await.unwrap();
+
+        // ALICE reverts and BOB has no account: nothing to report, no error
+        assert!(monitor.fetch_vault_balances().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn fetch_vault_balances_batches_through_multicall() {
+        // Multicall3 runs each inner call against the vault handler; BOB's
+        // read reverts inside the batch
+        let node = MockRpc::start(|method, params| {
+            let to: Address = serde_json::from_value(params[0]["to"].clone()).unwrap_or_default();
+            if method != "eth_call" || to != network::MULTICALL3 {
+                return vault_handler(method, params);
+            }
+            let data: Bytes = serde_json::from_value(params[0]["data"].clone()).unwrap();
+            let batch = IMulticall3::aggregate3Call::abi_decode(&data).unwrap();
+            let results: Vec<IMulticall3::Result> = batch
+                .calls
+                .iter()
+                .map(|call| {
+                    let child = ScholarFiVault::getChildAccountCall::abi_decode(&call.callData).unwrap()._child;
+                    let inner = json!([{ "to": call.target, "data": call.callData }]);
+                    match vault_handler("eth_call", &inner) {
+                        Ok(returned) if child != BOB => IMulticall3::Result {
+                            success: true,
+                            returnData: serde_json::from_value(returned).unwrap(),
+                        },
+                        _ => IMulticall3::Result {
+                            success: false,
+                            returnData: Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]),
+                        },
+                    }
+                })
+                .collect();
+            Ok(json!(Bytes::from(IMulticall3::aggregate3Call::abi_encode_returns(&results))))
+        })
+        .await;
+        let mut config = test_config(node.url());
+        config.multicall_address = Some(network::MULTICALL3);
+        config.multicall_batch_size = 100;
+        let mut monitor = RoflMonitor::new(config).unwrap();
+        monitor.sync_children().await.unwrap();
+
+        let balances = monitor.fetch_vault_balances().await.unwrap();
+        let eth_calls = node.calls().iter().filter(|(m, _)| m == "eth_call").count();
+        assert_eq!(eth_calls, 1, "both children in one batch");
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0].child_address, ALICE);
+    }
+
+    #[tokio::test]
+    async fn fatal_errors_abort_the_cycle() {
+        // Calling a non-contract returns empty data, which cannot decode
+        let node = MockRpc::start(|method, params| match method {
+            "eth_call" => Ok(json!("0x")),
+            _ => vault_handler(method, params),
+        })
+        .await;
+        let mut monitor = RoflMonitor::new(test_config(node.url())).unwrap();
+
+        let err = monitor.cycle().await.unwrap_err();
+        assert!(matches!(err, Error::Decode(_)), "{err}");
+        // Indexing progress from before the failure is still committed
+        assert_eq!(monitor.state.load().unwrap().children.len(), 2);
+    }
+
+    #[test]
+    fn retry_delay_backs_off_up_to_interval() {
+        let interval = Duration::from_secs(3600);
+        assert_eq!(retry_delay(1, interval), Duration::from_secs(15));
+        assert_eq!(retry_delay(3, interval), Duration::from_secs(60));
+        assert_eq!(retry_delay(20, interval), interval);
+    }
+
+    #[tokio::test]
+    async fn restart_resumes_from_committed_state() {
+        let node = MockRpc::start(vault_handler).await;
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = test_config(node.url());
+        config.state_path = dir.path().join("state.db").to_string_lossy().into_owned();
+
+        let mut monitor = RoflMonitor::new(config).unwrap();
+        monitor.sync_children().await.unwrap();
+        monitor.ledger.open(ALICE, 42, 1_700_000_000);
+        let snapshot = monitor.snapshot();
+        monitor.state.commit(&snapshot).unwrap();
+        drop(monitor);
+
+        let mut config = test_config(node.url());
+        config.state_path = dir.path().join("state.db").to_string_lossy().into_owned();
+        let restarted = RoflMonitor::new(config).unwrap();
+
+        assert_eq!(restarted.indexer.next_block(), 0x11);
+        assert_eq!(restarted.indexer.children().len(), 2);
+        assert_eq!(restarted.snapshot(), snapshot);
+    }
+
+    #[tokio::test]
+    async fn reorg_drops_children_and_their_growth() {
+        let chain = MockChain::start(10).await;
+        for (child, block) in [(ALICE, 3), (BOB, 9)] {
+            let data = ScholarFiVault::ChildAccountCreated {
+                child,
+                parent: PARENT,
+                timestamp: U256::from(1_700_000_000u64),
+            }
+            .encode_log_data();
+            chain.add_log(block, json!({ "address": VAULT, "topics": data.topics(), "data": data.data }));
+        }
+        let mut monitor = RoflMonitor::new(test_config(chain.url())).unwrap();
+        monitor.sync_children().await.unwrap();
+        monitor.ledger.open(ALICE, 1, 1_700_000_000);
+        monitor.ledger.open(BOB, 2, 1_700_000_000);
+
+        // BOB's creation block is replaced by a fork without it
+        chain.reorg(8, 12);
+        monitor.sync_children().await.unwrap();
+
+        let snapshot = monitor.snapshot();
+        assert_eq!(snapshot.children, BTreeMap::from([(ALICE, 3)]));
+        assert_eq!(snapshot.growth.keys().copied().collect::<Vec<_>>(), vec![ALICE]);
+        assert_eq!(snapshot.checkpoints[CELO], 13);
+        assert_eq!(snapshot.block_hashes[CELO].get(&12), Some(&chain.hash(12)));
+    }
+
+    #[tokio::test]
+    async fn deposits_are_recorded_once_across_restarts() {
+        let chain = MockChain::start(10).await;
+        add_vault_deposit(&chain, 2);
+        let oasis = MockRpc::start(oasis_handler).await;
+        let dir = tempfile::tempdir().unwrap();
+        let start = || {
+            let mut config = test_config(chain.url());
+            config.state_path = dir.path().join("state.db").to_string_lossy().into_owned();
+            let mut monitor = RoflMonitor::new(config).unwrap();
+            monitor.data_store = oasis_store(&oasis);
+            monitor
+        };
+        let sent = || oasis.calls().iter().filter(|(method, _)| method == "eth_sendRawTransaction").count();
+
+        let mut monitor = start();
+        monitor.mirror_deposits(&mut CycleStatus::default()).await.unwrap();
+        assert_eq!(sent(), 1);
+        let (_, estimate) = oasis.calls().into_iter().find(|(m, _)| m == "eth_estimateGas").unwrap();
+        let expected = contracts::ChildDataStore::recordDepositCall {
+            _childAddress: ALICE,
+            _additionalDeposit: U256::from(1_000u64),
+        };
+        assert_eq!(estimate[0]["data"], json!(Bytes::from(expected.abi_encode())));
+
+        // Seen again on the next cycle and after a restart, never re-sent
+        monitor.mirror_deposits(&mut CycleStatus::default()).await.unwrap();
+        drop(monitor);
+        let mut restarted = start();
+        let deposit = restarted.deposits.values().next().unwrap().clone();
+        assert!(deposit.recorded_tx.is_some(), "not committed");
+        restarted.vault_deposits = DepositWatcher::new(CELO, VAULT, DepositSource::Vault, 0, 1000, 4);
+        restarted.mirror_deposits(&mut CycleStatus::default()).await.unwrap();
+        assert_eq!(sent(), 1);
+        assert_eq!(restarted.deposits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn deposit_is_not_resent_after_a_failed_receipt_poll() {
+        let chain = MockChain::start(10).await;
+        add_vault_deposit(&chain, 0);
+        // The first transaction is broadcast, then the node fails every
+        // receipt poll; later it reports the nonce used by another tx
+        let dropped = Arc::new(AtomicU64::new(0));
+        let nonce = dropped.clone();
+        let oasis = MockRpc::start(move |method, params| match method {
+            "eth_getTransactionCount" => Ok(json!(U64::from(nonce.load(Ordering::SeqCst)))),
+            "eth_getTransactionReceipt" if nonce.load(Ordering::SeqCst) == 0 => Err(rpc::JsonRpcError {
+                code: -32000,
+                message: "header not found".to_string(),
+                data: None,
+            }),
+            "eth_getTransactionReceipt" => Ok(Value::Null),
+            _ => oasis_handler(method, params),
+        })
+        .await;
+        let mut monitor = RoflMonitor::new(test_config(chain.url())).unwrap();
+        monitor.data_store = oasis_store(&oasis);
+        let sent = || oasis.calls().iter().filter(|(method, _)| method == "eth_sendRawTransaction").count();
+
+        let mut status = CycleStatus::default();
+        monitor.mirror_deposits(&mut status).await.unwrap();
+        assert!(status.retry);
+        let recorded = monitor.deposits.values().next().unwrap().recorded_tx;
+        assert!(recorded.is_some());
+        let saved = monitor.state.load().unwrap();
+        assert_eq!(saved.deposits.values().next().unwrap().recorded_tx, recorded, "not committed");
+        assert_eq!(saved.pending_txs.len(), 1);
+        monitor.mirror_deposits(&mut CycleStatus::default()).await.unwrap();
+        assert_eq!(sent(), 1);
+
+        // Still no receipt, but nonce 0 has been used: the tx is gone and
+        // the deposit is recorded again
+        dropped.store(1, Ordering::SeqCst);
+        let failed = monitor.data_store.reconcile_pending().await.unwrap();
+        assert_eq!(failed, vec![recorded.unwrap()]);
+        monitor.forget_failed_deposits(&failed).unwrap();
+        assert_eq!(monitor.deposits.values().next().unwrap().recorded_tx, None);
+        monitor.mirror_deposits(&mut CycleStatus::default()).await.unwrap();
+        assert_eq!(sent(), 2);
+    }
+
+    #[tokio::test]
+    async fn reconciliation_repairs_age_verification_drift() {
+        const VERIFIER: Address = address!("00000000000000000000000000000000000000ee");
+        let head = Arc::new(AtomicU64::new(0x20));
+        let celo_head = head.clone();
+        let celo = MockRpc::start(move |method, params| match method {
+            "eth_blockNumber" => Ok(json!(U64::from(celo_head.load(Ordering::SeqCst)))),
+            "eth_getLogs" => Ok(json!([])),
+            "eth_call" => {
+                assert_eq!(params[0]["to"], json!(VERIFIER));
+                let data: Bytes = params[0]["data"].as_str().unwrap().parse().unwrap();
+                let call = ScholarFiAgeVerifier::isChildVerifiedCall::abi_decode(&data).unwrap();
+                let verified = call.childAddress == ALICE;
+                Ok(json!(Bytes::from(ScholarFiAgeVerifier::isChildVerifiedCall::abi_encode_returns(&verified))))
+            }
+            other => panic!("unexpected method {other}"),
+        })
+        .await;
+        let oasis = MockRpc::start(|method, params| match method {
+            // Neither child is marked on Oasis. Unsigned calls run from the
+            // zero address on Sapphire, so profiles are out of reach
+            "eth_call" => {
+                let data: Bytes = params[0]["data"].as_str().unwrap().parse().unwrap();
+                match contracts::ChildDataStore::isAgeVerifiedCall::abi_decode(&data) {
+                    Ok(_) => Ok(json!(Bytes::from(contracts::ChildDataStore::isAgeVerifiedCall::abi_encode_returns(
+                        &false
+                    )))),
+                    Err(_) => Err(reverted(contracts::ChildDataStore::Unauthorized::SELECTOR)),
+                }
+            }
+            _ => oasis_handler(method, params),
+        })
+        .await;
+        let mut config = test_config(celo.url());
+        config.age_verifier_address = Some(VERIFIER);
+        config.age_reconcile_interval_seconds = 3600;
+        let mut monitor = RoflMonitor::new(config).unwrap();
+        monitor.data_store = oasis_store(&oasis);
+        monitor.indexer.insert(ALICE, 1);
+        monitor.indexer.insert(BOB, 1);
+        let sent = || oasis.calls().iter().filter(|(method, _)| method == "eth_sendRawTransaction").count();
+
+        // ALICE drifted, but the Celo read is not confirmed yet
+        monitor.propagate_age_verification(&mut CycleStatus::default()).await.unwrap();
+        assert_eq!(monitor.verifications[&ALICE], Verification::seen_at(0x20));
+        assert!(!monitor.verifications.contains_key(&BOB));
+        assert_eq!(sent(), 0);
+
+        head.store(0x23, Ordering::SeqCst);
+        monitor.propagate_age_verification(&mut CycleStatus::default()).await.unwrap();
+        assert_eq!(sent(), 1);
+        assert!(monitor.verifications[&ALICE].marked_tx.is_some());
+        let (_, estimate) = oasis.calls().into_iter().find(|(m, _)| m == "eth_estimateGas").unwrap();
+        let expected = contracts::ChildDataStore::markAgeVerifiedCall { _childAddress: ALICE };
+        assert_eq!(estimate[0]["data"], json!(Bytes::from(expected.abi_encode())));
+        // Not due again within the interval
+        assert_eq!(oasis.calls().iter().filter(|(method, _)| method == "eth_call").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn overdue_deliveries_alert_once() {
+        let base = MockChain::start(10).await;
+        let celo = MockChain::start(10).await;
+        let mut config = test_config(celo.url());
+        config.base_rpc = vec![base.url().to_string()];
+        config.bridge_address = Some(address!("00000000000000000000000000000000000000b1"));
+        config.base_mailbox_address = Some(address!("00000000000000000000000000000000000000b2"));
+        config.celo_mailbox_address = Some(address!("00000000000000000000000000000000000000c2"));
+        config.delivery_timeout_seconds = 600;
+        let mut monitor = RoflMonitor::new(config).unwrap();
+        let dispatched = |age: u64| Delivery {
+            dispatched: Some(hyperlane::Sighting {
+                block: 1,
+                timestamp: unix_now() - age,
+            }),
+            ..Default::default()
+        };
+        monitor.deliveries.insert(B256::repeat_byte(0x01), dispatched(60));
+        monitor.deliveries.insert(B256::repeat_byte(0x02), dispatched(3_600));
+
+        monitor.track_deliveries(&mut CycleStatus::default()).await.unwrap();
+
+        assert!(!monitor.deliveries[&B256::repeat_byte(0x01)].alerted);
+        assert!(monitor.deliveries[&B256::repeat_byte(0x02)].alerted);
+        assert_eq!(monitor.metrics.undelivered.get(), 2);
+        assert_eq!(monitor.metrics.overdue.get(), 1);
+        let checkpoints = monitor.snapshot().checkpoints;
+        assert_eq!(checkpoints[BASE_DISPATCHES], 8);
+        assert_eq!(checkpoints[CELO_DELIVERIES], 8);
+    }
+
+    #[tokio::test]
+    async fn only_overdue_deliveries_are_simulated() {
+        // Nothing is confirmed yet, and every delivery lacks value
+        let node = MockRpc::start(|method, _| match method {
+            "eth_blockNumber" => Ok(json!("0x0")),
+            "eth_call" => Err(reverted(ScholarFiVault::ZeroAmount::SELECTOR)),
+            other => panic!("unexpected method {other}"),
+        })
+        .await;
+        let mut config = test_config(node.url());
+        config.base_rpc = vec![node.url().to_string()];
+        config.bridge_address = Some(address!("00000000000000000000000000000000000000b1"));
+        config.base_mailbox_address = Some(address!("00000000000000000000000000000000000000b2"));
+        config.celo_mailbox_address = Some(address!("00000000000000000000000000000000000000c2"));
+        config.delivery_timeout_seconds = 600;
+        config.confirmation_depth = 5;
+        let mut monitor = RoflMonitor::new(config).unwrap();
+        let dispatched = |age: u64| Delivery {
+            child: Some(ALICE),
+            message: Some(HyperlaneMessage {
+                version: message::VERSION,
+                nonce: 0,
+                origin: 84532,
+                sender: address!("00000000000000000000000000000000000000b1").into_word(),
+                destination: 11142220,
+                recipient: VAULT.into_word(),
+                body: (ALICE, PARENT).abi_encode_params().into(),
+            }),
+            dispatched: Some(hyperlane::Sighting {
+                block: 1,
+                timestamp: unix_now() - age,
+            }),
+            ..Default::default()
+        };
+        monitor.deliveries.insert(B256::repeat_byte(0x01), dispatched(60));
+        monitor.deliveries.insert(B256::repeat_byte(0x02), dispatched(3_600));
+
+        monitor.track_deliveries(&mut CycleStatus::default()).await.unwrap();
+
+        assert_eq!(node.calls().iter().filter(|(method, _)| method == "eth_call").count(), 1);
+        assert_eq!(
+            monitor.rejections,
+            BTreeMap::from([(B256::repeat_byte(0x02), HandleRevert::ZeroAmount)])
+        );
+        assert_eq!(monitor.metrics.unprocessable.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn report_reconciles_deposits_and_vault_solvency() {
+        let celo = MockRpc::start(|method, params| {
+            let deposit = json!(ScholarFiVault::FundsDeposited::SIGNATURE_HASH);
+            match method {
+                "eth_getLogs" if params[0]["topics"][0][0] == deposit => {
+                    let data = ScholarFiVault::FundsDeposited {
+                        child: ALICE,
+                        parent: PARENT,
+                        totalAmount: U256::from(10_000_000_000_000_000_000u128),
+                        vaultAmount: U256::from(3_000_000_000_000_000_000u128),
+                        spendingAmount: U256::from(7_000_000_000_000_000_000u128),
+                    }
+                    .encode_log_data();
+                    Ok(json!([{
+                        "address": VAULT,
+                        "topics": data.topics(),
+                        "data": data.data,
+                        "blockNumber": "0x6",
+                        "transactionHash": B256::repeat_byte(0x01),
+                        "logIndex": "0x0",
+                    }]))
+                }
+                // 9 CELO held against ALICE's 3 + 7
+                "eth_getBalance" => Ok(json!(U256::from(9_000_000_000_000_000_000u128))),
+                _ => vault_handler(method, params),
+            }
+        })
+        .await;
+        // ALICE's deposit reached Oasis, BOB never got a profile. Once
+        // `stale` is set, the store predates getTotalDeposited
+        let stale = Arc::new(AtomicBool::new(false));
+        let redeployed = stale.clone();
+        let oasis = MockRpc::start(move |method, params| {
+            assert_eq!(method, "eth_call");
+            // Sapphire runs unsigned calls from the zero address anyway
+            assert!(params[0]["from"].is_null(), "{}", params);
+            if redeployed.load(Ordering::SeqCst) {
+                return Err(reverted([]));
+            }
+            let data: Bytes = params[0]["data"].as_str().unwrap().parse().unwrap();
+            let call = contracts::ChildDataStore::getTotalDepositedCall::abi_decode(&data).unwrap();
+            if call._childAddress != ALICE {
+                return Err(reverted(contracts::ChildDataStore::ProfileNotFound::SELECTOR));
+            }
+            Ok(json!(Bytes::from(U256::from(10_000_000_000_000_000_000u128).abi_encode())))
+        })
+        .await;
+        let mut monitor = RoflMonitor::new(test_config(celo.url())).unwrap();
+        monitor.data_store = oasis_store(&oasis);
+
+        let report = monitor.reconciliation_report().await.unwrap();
+
+        assert_eq!(report.children.len(), 2);
+        assert_eq!(report.children[0].celo_deposits, U256::from(10_000_000_000_000_000_000u128));
+        assert_eq!(report.children[0].difference, "0");
+        assert_eq!(report.children[1].oasis_total_deposited, None);
+        assert_eq!(report.discrepancies, 0);
+        assert_eq!(report.vault.liabilities, U256::from(10_000_000_000_000_000_000u128));
+        assert_eq!(report.vault.shortfall, U256::from(1_000_000_000_000_000_000u128));
+        assert!(!report.is_consistent());
+
+        // An unreadable total still leaves a report, but not a consistent one
+        stale.store(true, Ordering::SeqCst);
+        let report = monitor.reconciliation_report().await.unwrap();
+        assert_eq!((report.unavailable, report.discrepancies), (2, 0));
+        assert_eq!(report.children[0].status, reconcile::ChildStatus::Unavailable);
+        assert!(!report.is_consistent());
+    }
+
+    #[tokio::test]
+    async fn vault_events_index_and_commit_children() {
+        // The vault answers, the Aave data provider (zero address) is rate limited
+        let node = MockRpc::start(|method, params| {
+            if method == "eth_call" && params[0]["to"] == json!(Address::ZERO) {
+                return Err(rpc::JsonRpcError {
+                    code: -32005,
+                    message: "limit exceeded".to_string(),
+                    data: None,
+                });
+            }
+            vault_handler(method, params)
+        })
+        .await;
+        let mut monitor = RoflMonitor::new(test_config(node.url())).unwrap();
+
+        monitor.process_children(&BTreeMap::from([(ALICE, 0x10)])).await.unwrap();
+
+        assert!(monitor.indexer.children().contains_key(&ALICE));
+        assert!(monitor.state.load().unwrap().children.contains_key(&ALICE), "not committed");
+    }
+
+    #[tokio::test]
+    async fn verify_networks_reports_every_mismatch() {
+        // Celo Sepolia everywhere: right for Celo, wrong for Sapphire, and
+        // the Base endpoint and the Celo fallback are down
+        let node = MockRpc::start(|_, _| Ok(json!("0xaa044c"))).await;
+        let mut config = test_config(node.url());
+        config.celo_rpc.push("http://127.0.0.1:1".to_string());
+        config.oasis_rpc = vec![node.url().to_string()];
+        let monitor = RoflMonitor::new(config).unwrap();
+
+        let err = monitor.verify_networks().await.unwrap_err().to_string();
+        assert!(!err.contains(&format!("Celo Sepolia RPC {}", node.url().trim_start_matches("http://"))), "{}", err);
+        assert!(err.contains("Celo Sepolia RPC 127.0.0.1:1 unreachable"), "{}", err);
+        assert!(err.contains("Sapphire Testnet RPC"), "{}", err);
+        assert!(err.contains("reports chain 11142220 (expected 23295)"), "{}", err);
+        assert!(err.contains("Base Sepolia RPC 127.0.0.1:1 unreachable"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn verify_deployment_reports_every_mismatch() {
+        let bridge = address!("00000000000000000000000000000000000000b1");
+        let verifier = address!("00000000000000000000000000000000000000a9");
+        let splitter = address!("00000000000000000000000000000000000000d5");
+        let (base_mailbox, celo_mailbox) = (Address::repeat_byte(0xb2), Address::repeat_byte(0xc2));
+        let node = MockRpc::start(move |method, params| {
+            let to: Address = serde_json::from_value(params[0]["to"].clone()).unwrap_or_default();
+            match method {
+                "eth_getCode" if params[0] == json!(splitter) => Ok(json!("0x")),
+                "eth_getCode" => Ok(json!("0x6080")),
+                // ChildDataStore, deployed before its access-free views
+                "eth_call" if to == Address::ZERO => Err(reverted([])),
+                "eth_call" => {
+                    let data: Bytes = serde_json::from_value(params[0]["data"].clone()).unwrap();
+                    let selector = [data[0], data[1], data[2], data[3]];
+                    let returns = match (to, selector) {
+                        (VAULT, ScholarFiVault::getHyperlaneConfigCall::SELECTOR) => {
+                            // Still authorizes the bridge it was first deployed with
+                            (celo_mailbox, Address::repeat_byte(0xb0)).abi_encode_params()
+                        }
+                        (VAULT, ScholarFiVault::getVerificationConfigCall::SELECTOR) => {
+                            (B256::repeat_byte(0x01), U256::from(18)).abi_encode_params()
+                        }
+                        (_, ScholarFiAgeVerifier::getVerificationConfigCall::SELECTOR) => {
+                            (B256::repeat_byte(0x02), U256::from(18)).abi_encode_params()
+                        }
+                        // Deployed with the Base domain instead of Celo's
+                        (_, contracts::ScholarFiBridge::getConfigCall::SELECTOR) => {
+                            (base_mailbox, 84532u32, VAULT).abi_encode_params()
+                        }
+                        other => panic!("unexpected call {:?}", other),
+                    };
+                    Ok(json!(Bytes::from(returns)))
+                }
+                _ => panic!("unexpected {}", method),
+            }
+        })
+        .await;
+        let mut config = test_config(node.url());
+        config.oasis_rpc = vec![node.url().to_string()];
+        config.base_rpc = vec![node.url().to_string()];
+        config.age_verifier_address = Some(verifier);
+        config.deposit_splitter_address = Some(splitter);
+        config.bridge_address = Some(bridge);
+        config.base_mailbox_address = Some(base_mailbox);
+        config.celo_mailbox_address = Some(celo_mailbox);
+        let monitor = RoflMonitor::new(config).unwrap();
+
+        let err = monitor.verify_deployment().await.unwrap_err().to_string();
+        let problems: Vec<&str> = err.lines().skip(1).collect();
+        assert_eq!(problems.len(), 6, "{}", err);
+        assert!(err.contains(&format!("ParentDepositSplitter {} on Base Sepolia has no code", splitter)), "{}", err);
+        assert!(
+            err.contains(&format!(
+                "ChildDataStore {} on Sapphire Testnet has no isAgeVerified(address)",
+                Address::ZERO
+            )),
+            "{}",
+            err
+        );
+        assert!(err.contains("has no getTotalDeposited(address)"), "{}", err);
+        assert!(
+            err.contains(&format!(
+                "returns authorized bridge {}, but bridge_address is {}",
+                Address::repeat_byte(0xb0),
+                bridge
+            )),
+            "{}",
+            err
+        );
+        assert!(
+            err.contains("ScholarFiBridge.getConfig() returns Celo domain 84532, but Celo Sepolia is domain 11142220"),
+            "{}",
+            err
+        );
+        assert!(err.contains("getVerificationConfig() disagrees"), "{}", err);
+        assert!(!err.contains("mailbox"), "{}", err);
+    }
+
+    #[test]
+    fn parses_command_line() {
+        let args = |list: &[&str]| Args::parse(list.iter().map(|s| s.to_string()));
+
+        assert_eq!(args(&[]).unwrap(), Args::default());
+        assert_eq!(
+            args(&["--config", "rofl.toml", "--network=local"]).unwrap(),
+            Args {
+                config: Some("rofl.toml".into()),
+                network: Some(Network::Local),
+                report: None,
+            }
+        );
+        assert_eq!(args(&["--report", "table"]).unwrap().report, Some(ReportFormat::Table));
+        assert!(args(&["--network", "alfajores"]).is_err());
+        assert!(args(&["--report=csv"]).is_err());
+        assert!(args(&["--config"]).is_err());
+        assert!(args(&["--verbose"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn cycle_records_metrics() {
+        // The vault answers, the Aave data provider (zero address) is rate limited
+        let node = MockRpc::start(|method, params| {
+            if method == "eth_call" && params[0]["to"] == json!(Address::ZERO) {
+                return Err(rpc::JsonRpcError {
+                    code: -32005,
+                    message: "limit exceeded".to_string(),
+                    data: None,
+                });
+            }
+            vault_handler(method, params)
+        })
+        .await;
+        let mut monitor = RoflMonitor::new(test_config(node.url())).unwrap();
+
+        assert!(!monitor.cycle().await.unwrap(), "Aave failure fails the cycle");
+
+        let text = monitor.metrics.render();
+        for expected in [
+            "scholarfi_vaults 1",
+            "scholarfi_vault_balance_wei 3000000000000000000",
+            "scholarfi_spending_balance_wei 7000000000000000000",
+            "scholarfi_cycles_total{outcome=\"failure\"} 1",
+            "scholarfi_last_successful_cycle_timestamp_seconds 0",
+            "scholarfi_rpc_errors_total{chain=\"celo\",method=\"eth_call\"} 1",
+            // Child, deposit and age verification indexing
+            "scholarfi_rpc_request_duration_seconds_count{chain=\"celo\",method=\"eth_getLogs\"} 3",
+        ] {
+            assert!(text.contains(expected), "missing {:?} in:\n{}", expected, text);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use prometheus::{
+    Encoder, Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+
+/// Prometheus series for the monitoring loop
+///
+/// One instance is shared (behind an `Arc`) by the monitor, its RPC clients
+/// and the HTTP server that serves `/metrics`. Each monitor owns its own
+/// registry rather than using the process-global one, so tests can run
+/// several monitors side by side.
+pub struct Metrics {
+    registry: Registry,
+    pub cycle_duration: Histogram,
+    pub cycles: IntCounterVec,
+    pub last_success: IntGauge,
+    pub rpc_latency: HistogramVec,
+    pub rpc_errors: IntCounterVec,
+    pub rpc_endpoint_health: GaugeVec,
+    pub rpc_disagreements: IntCounterVec,
+    pub vaults: IntGauge,
+    pub vault_balance: Gauge,
+    pub spending_balance: Gauge,
+    pub apy: Gauge,
+    pub growth_updates: IntCounterVec,
+    pub deposits_recorded: IntCounterVec,
+    pub age_verifications: IntCounterVec,
+    pub vault_events: IntCounterVec,
+    pub delivery_latency: Histogram,
+    pub undelivered: IntGauge,
+    pub overdue: IntGauge,
+    pub unprocessable: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("scholarfi".to_string()), None).expect("valid prefix");
+
+        let cycle_duration = Histogram::with_opts(
+            HistogramOpts::new("cycle_duration_seconds", "Duration of one monitoring cycle")
+                .buckets(vec![0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0]),
+        )
+        .expect("valid histogram");
+        let cycles = IntCounterVec::new(
+            Opts::new("cycles_total", "Completed monitoring cycles by outcome"),
+            &["outcome"],
+        )
+        .expect("valid counter");
+        let last_success = IntGauge::new(
+            "last_successful_cycle_timestamp_seconds",
+            "Unix time the last fully successful cycle finished",
+        )
+        .expect("valid gauge");
+        let rpc_latency = HistogramVec::new(
+            HistogramOpts::new("rpc_request_duration_seconds", "JSON-RPC request latency")
+                .buckets(vec![0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]),
+            &["chain", "method"],
+        )
+        .expect("valid histogram");
+        let rpc_errors = IntCounterVec::new(
+            Opts::new("rpc_errors_total", "Failed JSON-RPC requests"),
+            &["chain", "method"],
+        )
+        .expect("valid counter");
+        let rpc_endpoint_health = GaugeVec::new(
+            Opts::new("rpc_endpoint_health", "Smoothed success rate of each RPC endpoint (1 = healthy)"),
+            &["chain", "endpoint"],
+        )
+        .expect("valid gauge");
+        let rpc_disagreements = IntCounterVec::new(
+            Opts::new("rpc_disagreements_total", "Quorum reads where RPC endpoints returned different answers"),
+            &["chain", "method"],
+        )
+        .expect("valid counter");
+        let vaults = IntGauge::new("vaults", "Child vaults seen in the last cycle").expect("valid gauge");
+        let vault_balance = Gauge::new("vault_balance_wei", "Sum of locked vault balances").expect("valid gauge");
+        let spending_balance =
+            Gauge::new("spending_balance_wei", "Sum of spending balances").expect("valid gauge");
+        let apy = Gauge::new("aave_apy_ratio", "Current Aave supply APY (0.05 = 5%)").expect("valid gauge");
+        let growth_updates = IntCounterVec::new(
+            Opts::new("growth_updates_total", "updateVaultGrowth transactions by outcome"),
+            &["outcome"],
+        )
+        .expect("valid counter");
+        let deposits_recorded = IntCounterVec::new(
+            Opts::new("deposits_recorded_total", "recordDeposit transactions by outcome"),
+            &["outcome"],
+        )
+        .expect("valid counter");
+        let age_verifications = IntCounterVec::new(
+            Opts::new("age_verifications_total", "markAgeVerified transactions by outcome"),
+            &["outcome"],
+        )
+        .expect("valid counter");
+
+        let vault_events = IntCounterVec::new(
+            Opts::new("vault_events_total", "ScholarFiVault events received from the subscription"),
+            &["event"],
+        )
+        .expect("valid counter");
+
+        let delivery_latency = Histogram::with_opts(
+            HistogramOpts::new(
+                "hyperlane_delivery_seconds",
+                "Time from Hyperlane dispatch on Base to delivery on Celo of bridged deposits",
+            )
+            .buckets(vec![30.0, 60.0, 120.0, 300.0, 600.0, 1200.0, 1800.0, 3600.0, 7200.0]),
+        )
+        .expect("valid histogram");
+        let undelivered = IntGauge::new(
+            "hyperlane_undelivered_messages",
+            "Bridged deposits dispatched on Base and not yet delivered on Celo",
+        )
+        .expect("valid gauge");
+        let overdue = IntGauge::new(
+            "hyperlane_overdue_messages",
+            "Undelivered bridged deposits older than the delivery timeout",
+        )
+        .expect("valid gauge");
+        let unprocessable = IntGauge::new(
+            "hyperlane_unprocessable_messages",
+            "Overdue bridged deposits ScholarFiVault.handle would revert on",
+        )
+        .expect("valid gauge");
+
+        let metrics = Self {
+            registry,
+            cycle_duration,
+            cycles,
+            last_success,
+            rpc_latency,
+            rpc_errors,
+            rpc_endpoint_health,
+            rpc_disagreements,
+            vaults,
+            vault_balance,
+            spending_balance,
+            apy,
+            growth_updates,
+            deposits_recorded,
+            age_verifications,
+            vault_events,
+            delivery_latency,
+            undelivered,
+            overdue,
+            unprocessable,
+        };
+        metrics.register();
+        metrics
+    }
+
+    fn register(&self) {
+        let collectors: [Box<dyn prometheus::core::Collector>; 19] = [
+            Box::new(self.cycle_duration.clone()),
+            Box::new(self.cycles.clone()),
+            Box::new(self.last_success.clone()),
+            Box::new(self.rpc_latency.clone()),
+            Box::new(self.rpc_errors.clone()),
+            Box::new(self.rpc_endpoint_health.clone()),
+            Box::new(self.rpc_disagreements.clone()),
+            Box::new(self.vaults.clone()),
+            Box::new(self.vault_balance.clone()),
+            Box::new(self.spending_balance.clone()),
+            Box::new(self.apy.clone()),
+            Box::new(self.growth_updates.clone()),
+            Box::new(self.deposits_recorded.clone()),
+            Box::new(self.age_verifications.clone()),
+            Box::new(self.vault_events.clone()),
+            Box::new(self.delivery_latency.clone()),
+            Box::new(self.undelivered.clone()),
+            Box::new(self.overdue.clone()),
+            Box::new(self.unprocessable.clone()),
+        ];
+        for collector in collectors {
+            self.registry.register(collector).expect("unique metric names");
+        }
+    }
+
+    /// Everything in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding cannot fail");
+        String::from_utf8(buffer).expect("text encoding is UTF-8")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_prefixed_series() {
+        let metrics = Metrics::new();
+        metrics.cycles.with_label_values(&["success"]).inc();
+        metrics.rpc_errors.with_label_values(&["celo", "eth_call"]).inc_by(2);
+        metrics.apy.set(0.0408);
+
+        let text = metrics.render();
+        assert!(text.contains("scholarfi_cycles_total{outcome=\"success\"} 1"), "{}", text);
+        assert!(text.contains("scholarfi_rpc_errors_total{chain=\"celo\",method=\"eth_call\"} 2"));
+        assert!(text.contains("scholarfi_aave_apy_ratio 0.0408"));
+        assert!(text.contains("# TYPE scholarfi_cycle_duration_seconds histogram"));
+    }
+}
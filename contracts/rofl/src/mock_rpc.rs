@@ -0,0 +1,249 @@
+//! Local JSON-RPC server for tests
+//!
+//! Each request is dispatched to a handler closure that returns either a
+//! `result` value or a `JsonRpcError`. Every call is recorded so tests can
+//! assert on what the monitor actually sent.
+//!
+//! Faults queued with `inject` are applied to the next HTTP requests, one
+//! per request, before the handler sees them.
+//!
+//! `MockChain` builds on it to serve a chain of blocks that tests can
+//! reorganize.
+
+use crate::rpc::JsonRpcError;
+use alloy_primitives::{hex, B256, U64};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::header::RETRY_AFTER;
+use hyper::{Body, Request, Response, Server, StatusCode};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+type Handler = dyn Fn(&str, &Value) -> Result<Value, JsonRpcError> + Send + Sync;
+
+/// Misbehaviour for a single HTTP request
+#[derive(Debug, Clone, Copy)]
+pub enum Fault {
+    /// Close the connection without answering
+    Drop,
+    /// Answer normally after a pause
+    Delay(Duration),
+    /// HTTP 429, with a `Retry-After` in seconds if given
+    TooManyRequests { retry_after: Option<u64> },
+}
+
+pub struct MockRpc {
+    url: String,
+    calls: Arc<Mutex<Vec<(String, Value)>>>,
+    faults: Arc<Mutex<VecDeque<Fault>>>,
+}
+
+impl MockRpc {
+    pub async fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&str, &Value) -> Result<Value, JsonRpcError> + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock rpc");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handler: Arc<Handler> = Arc::new(handler);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let faults = Arc::new(Mutex::new(VecDeque::new()));
+
+        let service_calls = calls.clone();
+        let service_faults = faults.clone();
+        let make_service = make_service_fn(move |_| {
+            let handler = handler.clone();
+            let calls = service_calls.clone();
+            let faults = service_faults.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                    let handler = handler.clone();
+                    let calls = calls.clone();
+                    let fault = faults.lock().unwrap().pop_front();
+                    async move {
+                        match fault {
+                            // An error from the service makes hyper drop the connection
+                            Some(Fault::Drop) => return Err("dropped"),
+                            Some(Fault::Delay(pause)) => tokio::time::sleep(pause).await,
+                            Some(Fault::TooManyRequests { retry_after }) => {
+                                let mut response = Response::builder().status(StatusCode::TOO_MANY_REQUESTS);
+                                if let Some(secs) = retry_after {
+                                    response = response.header(RETRY_AFTER, secs);
+                                }
+                                return Ok(response.body(Body::empty()).unwrap());
+                            }
+                            None => {}
+                        }
+                        let bytes = hyper::body::to_bytes(req.into_body()).await.unwrap();
+                        let body: Value = serde_json::from_slice(&bytes).unwrap();
+                        let reply = match body {
+                            Value::Array(batch) => Value::Array(
+                                batch.iter().map(|r| dispatch(&*handler, &calls, r)).collect(),
+                            ),
+                            single => dispatch(&*handler, &calls, &single),
+                        };
+                        Ok(Response::new(Body::from(reply.to_string())))
+                    }
+                }))
+            }
+        });
+
+        let server = Server::from_tcp(listener).unwrap().serve(make_service);
+        tokio::spawn(server);
+
+        Self { url, calls, faults }
+    }
+
+    /// Apply `faults` to the next requests, in order
+    pub fn inject(&self, faults: impl IntoIterator<Item = Fault>) {
+        self.faults.lock().unwrap().extend(faults);
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Methods and params received so far, in arrival order
+    pub fn calls(&self) -> Vec<(String, Value)> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+/// The error a node returns for a call that reverted with `data`
+pub fn reverted(data: impl AsRef<[u8]>) -> JsonRpcError {
+    JsonRpcError {
+        code: 3,
+        message: "execution reverted".to_string(),
+        data: Some(json!(hex::encode_prefixed(data))),
+    }
+}
+
+fn dispatch(handler: &Handler, calls: &Mutex<Vec<(String, Value)>>, request: &Value) -> Value {
+    let method = request["method"].as_str().unwrap_or_default();
+    let params = &request["params"];
+    calls.lock().unwrap().push((method.to_string(), params.clone()));
+
+    match handler(method, params) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+        Err(e) => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": e.code, "message": e.message, "data": e.data },
+        }),
+    }
+}
+
+/// Timestamp of a `MockChain`'s block 0; each block comes one second later
+pub const GENESIS_TIMESTAMP: u64 = 1_700_000_000;
+
+/// A scripted chain served over JSON-RPC, for reorg tests
+///
+/// Block hashes encode the fork a block belongs to, so replacing blocks
+/// with `reorg` gives them new hashes while the blocks below keep theirs.
+/// Serves `eth_blockNumber`, `eth_getBlockByNumber` and `eth_getLogs`.
+pub struct MockChain {
+    node: MockRpc,
+    state: Arc<Mutex<ChainState>>,
+}
+
+#[derive(Default)]
+struct ChainState {
+    hashes: Vec<B256>,
+    forks: u64,
+    /// Block number and log object, without block fields
+    logs: Vec<(u64, Value)>,
+}
+
+impl ChainState {
+    fn extend_to(&mut self, head: u64) {
+        for number in self.hashes.len() as u64..=head {
+            let mut hash = B256::ZERO;
+            hash[..8].copy_from_slice(&self.forks.to_be_bytes());
+            hash[24..].copy_from_slice(&number.to_be_bytes());
+            self.hashes.push(hash);
+        }
+    }
+
+    fn header(&self, number: u64) -> Value {
+        match self.hashes.get(number as usize) {
+            Some(hash) => json!({
+                "number": U64::from(number),
+                "hash": hash,
+                "parentHash": number.checked_sub(1).map_or(B256::ZERO, |parent| self.hashes[parent as usize]),
+                "timestamp": U64::from(GENESIS_TIMESTAMP + number),
+            }),
+            None => Value::Null,
+        }
+    }
+
+    fn logs(&self, filter: &Value) -> Value {
+        let block = |key: &str| serde_json::from_value::<U64>(filter[key].clone()).unwrap().to::<u64>();
+        let (from, to) = (block("fromBlock"), block("toBlock"));
+        let topics = &filter["topics"][0];
+        let logs = self
+            .logs
+            .iter()
+            .filter(|(number, _)| (from..=to).contains(number) && (*number as usize) < self.hashes.len())
+            .filter(|(_, log)| log["address"] == filter["address"])
+            .filter(|(_, log)| topics.as_array().is_some_and(|topics| topics.contains(&log["topics"][0])))
+            .map(|(number, log)| {
+                let mut log = log.clone();
+                log["blockNumber"] = json!(U64::from(*number));
+                log["blockHash"] = json!(self.hashes[*number as usize]);
+                log
+            })
+            .collect();
+        Value::Array(logs)
+    }
+}
+
+impl MockChain {
+    /// Start with blocks `0..=head`
+    pub async fn start(head: u64) -> Self {
+        let state = Arc::new(Mutex::new(ChainState::default()));
+        state.lock().unwrap().extend_to(head);
+
+        let chain = state.clone();
+        let node = MockRpc::start(move |method, params| {
+            let chain = chain.lock().unwrap();
+            match method {
+                "eth_blockNumber" => Ok(json!(U64::from(chain.hashes.len() - 1))),
+                "eth_getBlockByNumber" => {
+                    let number: U64 = serde_json::from_value(params[0].clone()).unwrap();
+                    Ok(chain.header(number.to()))
+                }
+                "eth_getLogs" => Ok(chain.logs(&params[0])),
+                other => panic!("unexpected method {other}"),
+            }
+        })
+        .await;
+
+        Self { node, state }
+    }
+
+    pub fn url(&self) -> &str {
+        self.node.url()
+    }
+
+    pub fn hash(&self, number: u64) -> B256 {
+        self.state.lock().unwrap().hashes[number as usize]
+    }
+
+    /// Emit `log` (without block fields) in block `number`
+    pub fn add_log(&self, number: u64, log: Value) {
+        self.state.lock().unwrap().logs.push((number, log));
+    }
+
+    /// Replace blocks from `from` on with a new fork reaching `head`,
+    /// dropping the logs they held
+    pub fn reorg(&self, from: u64, head: u64) {
+        let mut chain = self.state.lock().unwrap();
+        chain.forks += 1;
+        chain.hashes.truncate(from as usize);
+        chain.logs.retain(|(number, _)| *number < from);
+        chain.extend_to(head);
+    }
+}
@@ -0,0 +1,245 @@
+use crate::rpc::RpcClient;
+use alloy_primitives::{address, Address};
+use std::fmt;
+use std::str::FromStr;
+
+/// One chain the monitor talks to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainProfile {
+    pub name: &'static str,
+    pub rpc: &'static str,
+    pub chain_id: u64,
+    /// Hyperlane domain ID (equal to the chain ID on every chain we use)
+    pub hyperlane_domain: u32,
+    pub mailbox: Option<Address>,
+    /// Multicall3, where deployed
+    pub multicall3: Option<Address>,
+}
+
+/// Scholar-Fi contracts deployed on a network, where known
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deployment {
+    pub scholar_fi_vault: Option<Address>,
+    pub age_verifier: Option<Address>,
+    pub child_data_store: Option<Address>,
+    pub deposit_splitter: Option<Address>,
+}
+
+/// Chains and deployed contracts of one Scholar-Fi environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetworkProfile {
+    pub celo: ChainProfile,
+    pub oasis: ChainProfile,
+    pub base: ChainProfile,
+    pub deployment: Deployment,
+}
+
+/// Named environment, selected with `--network`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Network {
+    /// Celo Sepolia, Sapphire testnet and Base Sepolia
+    #[default]
+    Testnet,
+    Mainnet,
+    /// Local dev nodes (anvil and sapphire-localnet)
+    Local,
+}
+
+/// Multicall3's canonical address, the same on every chain it is deployed to
+pub const MULTICALL3: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+const TESTNET: NetworkProfile = NetworkProfile {
+    celo: ChainProfile {
+        name: "Celo Sepolia",
+        rpc: "https://forno.celo-sepolia.celo-testnet.org",
+        chain_id: 11142220,
+        hyperlane_domain: 11142220,
+        mailbox: Some(address!("D0680F80F4f947968206806C2598Cbc5b6FE5b03")),
+        multicall3: Some(MULTICALL3),
+    },
+    oasis: ChainProfile {
+        name: "Sapphire Testnet",
+        rpc: "https://testnet.sapphire.oasis.io",
+        chain_id: 23295,
+        hyperlane_domain: 23295,
+        mailbox: None,
+        multicall3: Some(MULTICALL3),
+    },
+    base: ChainProfile {
+        name: "Base Sepolia",
+        rpc: "https://sepolia.base.org",
+        chain_id: 84532,
+        hyperlane_domain: 84532,
+        mailbox: Some(address!("6966b0E55883d49BFB24539356a2f8A673E02039")),
+        multicall3: Some(MULTICALL3),
+    },
+    // Addresses from the top-level README
+    deployment: Deployment {
+        scholar_fi_vault: None,
+        age_verifier: Some(address!("a4Ca603a1BEb03F1C11bdeA90227855f67DFf796")),
+        // The README's ChildDataStore predates isAgeVerified and
+        // getTotalDeposited and has to be redeployed, so there is no default
+        child_data_store: None,
+        deposit_splitter: Some(address!("9eC1c21F18a24319C2071603B04E38117C30eecA")),
+    },
+};
+
+const MAINNET: NetworkProfile = NetworkProfile {
+    celo: ChainProfile {
+        name: "Celo",
+        rpc: "https://forno.celo.org",
+        chain_id: 42220,
+        hyperlane_domain: 42220,
+        mailbox: Some(address!("50da3B3907A08a24fe4999F4Dcf337E8dC7954bb")),
+        multicall3: Some(MULTICALL3),
+    },
+    oasis: ChainProfile {
+        name: "Sapphire",
+        rpc: "https://sapphire.oasis.io",
+        chain_id: 23294,
+        hyperlane_domain: 23294,
+        mailbox: None,
+        multicall3: Some(MULTICALL3),
+    },
+    base: ChainProfile {
+        name: "Base",
+        rpc: "https://mainnet.base.org",
+        chain_id: 8453,
+        hyperlane_domain: 8453,
+        mailbox: Some(address!("eA87ae93Fa0019a82A727bfd3eBd1cFCa8f64f1D")),
+        multicall3: Some(MULTICALL3),
+    },
+    // Not deployed to mainnet yet; addresses must come from the config
+    deployment: Deployment {
+        scholar_fi_vault: None,
+        age_verifier: None,
+        child_data_store: None,
+        deposit_splitter: None,
+    },
+};
+
+const LOCAL: NetworkProfile = NetworkProfile {
+    celo: ChainProfile {
+        name: "Local Celo",
+        rpc: "http://127.0.0.1:8545",
+        chain_id: 31337,
+        hyperlane_domain: 31337,
+        mailbox: None,
+        multicall3: None,
+    },
+    oasis: ChainProfile {
+        name: "Sapphire Localnet",
+        rpc: "http://127.0.0.1:8547",
+        chain_id: 23293,
+        hyperlane_domain: 23293,
+        mailbox: None,
+        multicall3: None,
+    },
+    base: ChainProfile {
+        name: "Local Base",
+        rpc: "http://127.0.0.1:8546",
+        chain_id: 31338,
+        hyperlane_domain: 31338,
+        mailbox: None,
+        multicall3: None,
+    },
+    deployment: Deployment {
+        scholar_fi_vault: None,
+        age_verifier: None,
+        child_data_store: None,
+        deposit_splitter: None,
+    },
+};
+
+impl Network {
+    pub fn profile(self) -> &'static NetworkProfile {
+        match self {
+            Network::Testnet => &TESTNET,
+            Network::Mainnet => &MAINNET,
+            Network::Local => &LOCAL,
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "testnet" => Ok(Network::Testnet),
+            "mainnet" => Ok(Network::Mainnet),
+            "local" => Ok(Network::Local),
+            other => Err(format!("unknown network {:?} (expected testnet, mainnet or local)", other)),
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Network::Testnet => "testnet",
+            Network::Mainnet => "mainnet",
+            Network::Local => "local",
+        })
+    }
+}
+
+impl ChainProfile {
+    /// Check that `rpc` serves this chain. A mismatch is reported as an
+    /// error message rather than a transport error so the caller can list
+    /// every misconfigured endpoint at once.
+    pub async fn verify(&self, rpc: &RpcClient) -> Result<(), String> {
+        match rpc.chain_id().await {
+            Ok(id) if id == self.chain_id => Ok(()),
+            Ok(id) => Err(format!(
+                "{} RPC {} reports chain {} (expected {})",
+                self.name,
+                rpc.host(),
+                id,
+                self.chain_id
+            )),
+            Err(e) => Err(format!("{} RPC {} unreachable: {}", self.name, rpc.host(), e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc::MockRpc;
+    use serde_json::json;
+
+    #[test]
+    fn parses_names_round_trip() {
+        for network in [Network::Testnet, Network::Mainnet, Network::Local] {
+            assert_eq!(network.to_string().parse::<Network>(), Ok(network));
+        }
+        assert!("alfajores".parse::<Network>().is_err());
+    }
+
+    #[test]
+    fn testnet_matches_bridge_domains() {
+        let profile = Network::Testnet.profile();
+        assert_eq!(profile.base.hyperlane_domain, 84532);
+        assert_eq!(profile.celo.hyperlane_domain, 11142220);
+    }
+
+    #[tokio::test]
+    async fn verify_rejects_wrong_chain() {
+        // Alfajores instead of Celo Sepolia
+        let node = MockRpc::start(|method, _| {
+            assert_eq!(method, "eth_chainId");
+            Ok(json!("0xaef3"))
+        })
+        .await;
+        let rpc = RpcClient::new(reqwest::Client::new(), node.url());
+
+        let err = Network::Testnet.profile().celo.verify(&rpc).await.unwrap_err();
+        assert!(err.contains("reports chain 44787 (expected 11142220)"), "{}", err);
+        assert!(!err.contains(node.url()), "{}", err);
+
+        let node = MockRpc::start(|_, _| Ok(json!("0xaa044c"))).await;
+        let rpc = RpcClient::new(reqwest::Client::new(), node.url());
+        assert_eq!(Network::Testnet.profile().celo.verify(&rpc).await, Ok(()));
+    }
+}
@@ -0,0 +1,375 @@
+use crate::error::Error;
+use alloy_primitives::{Address, U256};
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// Cross-chain reconciliation of deposits and balances
+///
+/// Every deposit through ParentDepositSplitter on Base and ScholarFiVault on
+/// Celo is mirrored into `ChildDataStore.totalDeposited` on Oasis, so each
+/// child's Oasis total should equal the sum of its deposits on both chains.
+/// Separately, the vault's native balance must cover the vault and spending
+/// balances it owes every child. Amounts are in wei and serialized as
+/// decimal strings.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct ReconciliationReport {
+    pub generated_at: u64,
+    pub children: Vec<ChildReconciliation>,
+    pub vault: VaultSolvency,
+    /// Children whose Oasis total disagrees with their deposits
+    pub discrepancies: usize,
+    /// Children whose Oasis total could not be read, so were not compared
+    pub unavailable: usize,
+}
+
+#[derive(Debug, Default, Serialize, PartialEq, Eq)]
+pub struct ChildReconciliation {
+    pub child: Address,
+    #[serde(serialize_with = "decimal")]
+    pub base_deposits: U256,
+    #[serde(serialize_with = "decimal")]
+    pub celo_deposits: U256,
+    /// `None` when the child has no profile on Oasis, or it was unreadable
+    #[serde(serialize_with = "optional_decimal")]
+    pub oasis_total_deposited: Option<U256>,
+    #[serde(serialize_with = "decimal")]
+    pub vault_balance: U256,
+    #[serde(serialize_with = "decimal")]
+    pub spending_balance: U256,
+    /// Oasis total minus the deposits on Base and Celo, signed; `-` when
+    /// the Oasis total is unavailable
+    pub difference: String,
+    pub status: ChildStatus,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChildStatus {
+    #[default]
+    Ok,
+    /// Oasis records more or less than was deposited
+    Mismatch,
+    /// Deposits exist but Oasis has no profile to record them in
+    MissingProfile,
+    /// The Oasis profile could not be read
+    Unavailable,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct VaultSolvency {
+    pub address: Address,
+    #[serde(serialize_with = "decimal")]
+    pub native_balance: U256,
+    /// Sum of every child's vault and spending balance
+    #[serde(serialize_with = "decimal")]
+    pub liabilities: U256,
+    /// How much the balance falls short of the liabilities, zero if solvent
+    #[serde(serialize_with = "decimal")]
+    pub shortfall: U256,
+    pub solvent: bool,
+}
+
+impl ReconciliationReport {
+    /// Whether all three chains agree. A child whose Oasis total could not
+    /// be read was not compared, so it counts against consistency too.
+    pub fn is_consistent(&self) -> bool {
+        self.discrepancies == 0 && self.unavailable == 0 && self.vault.solvent
+    }
+}
+
+/// Collects per-child figures from each chain, in any order
+#[derive(Default)]
+pub struct ReportBuilder {
+    children: BTreeMap<Address, ChildReconciliation>,
+    unavailable: BTreeSet<Address>,
+}
+
+impl ReportBuilder {
+    fn entry(&mut self, child: Address) -> &mut ChildReconciliation {
+        self.children.entry(child).or_insert_with(|| ChildReconciliation {
+            child,
+            ..Default::default()
+        })
+    }
+
+    /// Report on `child` even if it has no deposits or account
+    pub fn include(&mut self, child: Address) {
+        self.entry(child);
+    }
+
+    pub fn base_deposit(&mut self, child: Address, amount: U256) {
+        let entry = self.entry(child);
+        entry.base_deposits = entry.base_deposits.saturating_add(amount);
+    }
+
+    pub fn celo_deposit(&mut self, child: Address, amount: U256) {
+        let entry = self.entry(child);
+        entry.celo_deposits = entry.celo_deposits.saturating_add(amount);
+    }
+
+    /// Balances from the child's vault account on Celo
+    pub fn account(&mut self, child: Address, vault_balance: U256, spending_balance: U256) {
+        let entry = self.entry(child);
+        entry.vault_balance = vault_balance;
+        entry.spending_balance = spending_balance;
+    }
+
+    /// `totalDeposited` from the child's Oasis profile, `None` without one
+    pub fn oasis_total(&mut self, child: Address, total: Option<U256>) {
+        self.entry(child).oasis_total_deposited = total;
+    }
+
+    /// The child's Oasis profile could not be read
+    pub fn oasis_unavailable(&mut self, child: Address) {
+        self.entry(child).oasis_total_deposited = None;
+        self.unavailable.insert(child);
+    }
+
+    /// Children reported on so far
+    pub fn children(&self) -> Vec<Address> {
+        self.children.keys().copied().collect()
+    }
+
+    pub fn finish(self, vault: Address, native_balance: U256, generated_at: u64) -> ReconciliationReport {
+        let mut children: Vec<ChildReconciliation> = self.children.into_values().collect();
+        for child in &mut children {
+            if self.unavailable.contains(&child.child) {
+                child.difference = "-".to_string();
+                child.status = ChildStatus::Unavailable;
+                continue;
+            }
+            let deposited = child.base_deposits.saturating_add(child.celo_deposits);
+            let recorded = child.oasis_total_deposited.unwrap_or_default();
+            child.difference = match recorded.cmp(&deposited) {
+                std::cmp::Ordering::Equal => "0".to_string(),
+                std::cmp::Ordering::Greater => format!("+{}", recorded - deposited),
+                std::cmp::Ordering::Less => format!("-{}", deposited - recorded),
+            };
+            child.status = match child.oasis_total_deposited {
+                None if deposited.is_zero() => ChildStatus::Ok,
+                None => ChildStatus::MissingProfile,
+                Some(_) if recorded == deposited => ChildStatus::Ok,
+                Some(_) => ChildStatus::Mismatch,
+            };
+        }
+
+        let liabilities = children.iter().fold(U256::ZERO, |sum, child| {
+            sum.saturating_add(child.vault_balance).saturating_add(child.spending_balance)
+        });
+        ReconciliationReport {
+            generated_at,
+            discrepancies: children
+                .iter()
+                .filter(|child| matches!(child.status, ChildStatus::Mismatch | ChildStatus::MissingProfile))
+                .count(),
+            unavailable: children.iter().filter(|child| child.status == ChildStatus::Unavailable).count(),
+            children,
+            vault: VaultSolvency {
+                address: vault,
+                native_balance,
+                liabilities,
+                shortfall: liabilities.saturating_sub(native_balance),
+                solvent: native_balance >= liabilities,
+            },
+        }
+    }
+}
+
+/// How `--report` prints the reconciliation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Table,
+}
+
+impl FromStr for ReportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(ReportFormat::Json),
+            "table" => Ok(ReportFormat::Table),
+            other => Err(Error::Config(format!("unknown report format {:?} (expected json or table)", other))),
+        }
+    }
+}
+
+/// Human-readable table, one row per child, then the vault's solvency
+impl fmt::Display for ReconciliationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = ["child", "base", "celo", "oasis", "difference", "vault", "spending", "status"];
+        let rows: Vec<[String; 8]> = self
+            .children
+            .iter()
+            .map(|child| {
+                [
+                    child.child.to_string(),
+                    child.base_deposits.to_string(),
+                    child.celo_deposits.to_string(),
+                    child.oasis_total_deposited.map_or("-".to_string(), |total| total.to_string()),
+                    child.difference.clone(),
+                    child.vault_balance.to_string(),
+                    child.spending_balance.to_string(),
+                    match child.status {
+                        ChildStatus::Ok => "ok",
+                        ChildStatus::Mismatch => "MISMATCH",
+                        ChildStatus::MissingProfile => "NO PROFILE",
+                        ChildStatus::Unavailable => "UNREADABLE",
+                    }
+                    .to_string(),
+                ]
+            })
+            .collect();
+
+        let mut widths = header.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.len());
+            }
+        }
+        let line = |f: &mut fmt::Formatter<'_>, cells: &[&str]| -> fmt::Result {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(widths)
+                .enumerate()
+                // Addresses and status left-aligned, amounts right-aligned
+                .map(|(column, (cell, width))| match column {
+                    0 | 7 => format!("{:<width$}", cell),
+                    _ => format!("{:>width$}", cell),
+                })
+                .collect();
+            writeln!(f, "{}", padded.join("  ").trim_end())
+        };
+
+        line(f, &header)?;
+        for row in &rows {
+            line(f, &row.each_ref().map(String::as_str))?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{} of {} children disagree with Oasis",
+            self.discrepancies,
+            self.children.len()
+        )?;
+        if self.unavailable > 0 {
+            writeln!(
+                f,
+                "{} of {} children could not be read from Oasis",
+                self.unavailable,
+                self.children.len()
+            )?;
+        }
+        let vault = &self.vault;
+        write!(
+            f,
+            "Vault {} holds {} wei against {} wei of child balances: ",
+            vault.address, vault.native_balance, vault.liabilities
+        )?;
+        match vault.solvent {
+            true => writeln!(f, "solvent"),
+            false => writeln!(f, "SHORT {} wei", vault.shortfall),
+        }
+    }
+}
+
+fn decimal<S: Serializer>(amount: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(amount)
+}
+
+fn optional_decimal<S: Serializer>(amount: &Option<U256>, serializer: S) -> Result<S::Ok, S::Error> {
+    match amount {
+        Some(amount) => serializer.collect_str(amount),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use serde_json::json;
+
+    const VAULT: Address = address!("00000000000000000000000000000000000000aa");
+    const ALICE: Address = address!("0000000000000000000000000000000000000001");
+    const BOB: Address = address!("0000000000000000000000000000000000000002");
+    const CAROL: Address = address!("0000000000000000000000000000000000000003");
+    const DAVE: Address = address!("0000000000000000000000000000000000000004");
+
+    fn sample() -> ReconciliationReport {
+        let mut builder = ReportBuilder::default();
+        builder.base_deposit(ALICE, U256::from(1_000u64));
+        builder.celo_deposit(ALICE, U256::from(500u64));
+        builder.base_deposit(ALICE, U256::from(250u64));
+        builder.oasis_total(ALICE, Some(U256::from(1_750u64)));
+        builder.account(ALICE, U256::from(525u64), U256::from(1_225u64));
+        // One Celo deposit never made it to Oasis
+        builder.celo_deposit(BOB, U256::from(300u64));
+        builder.celo_deposit(BOB, U256::from(200u64));
+        builder.oasis_total(BOB, Some(U256::from(300u64)));
+        builder.account(BOB, U256::from(150u64), U256::from(350u64));
+        builder.celo_deposit(CAROL, U256::from(40u64));
+        builder.oasis_total(CAROL, None);
+        builder.celo_deposit(DAVE, U256::from(60u64));
+        builder.oasis_unavailable(DAVE);
+        builder.finish(VAULT, U256::from(2_000u64), 1_700_000_000)
+    }
+
+    #[test]
+    fn flags_per_child_discrepancies_and_shortfall() {
+        let report = sample();
+
+        let statuses: Vec<_> = report.children.iter().map(|c| (c.child, c.status, c.difference.as_str())).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (ALICE, ChildStatus::Ok, "0"),
+                (BOB, ChildStatus::Mismatch, "-200"),
+                (CAROL, ChildStatus::MissingProfile, "-40"),
+                (DAVE, ChildStatus::Unavailable, "-"),
+            ]
+        );
+        assert_eq!(report.discrepancies, 2);
+        assert_eq!(report.unavailable, 1);
+        // 525 + 1225 + 150 + 350 owed against 2000 held
+        assert_eq!(report.vault.liabilities, U256::from(2_250u64));
+        assert_eq!(report.vault.shortfall, U256::from(250u64));
+        assert!(!report.is_consistent());
+    }
+
+    #[test]
+    fn unreadable_children_are_not_consistent() {
+        let mut builder = ReportBuilder::default();
+        builder.celo_deposit(ALICE, U256::from(100u64));
+        builder.account(ALICE, U256::from(30u64), U256::from(70u64));
+        builder.oasis_unavailable(ALICE);
+        let report = builder.finish(VAULT, U256::from(100u64), 1_700_000_000);
+
+        assert_eq!((report.discrepancies, report.unavailable), (0, 1));
+        assert!(report.vault.solvent);
+        assert!(!report.is_consistent());
+    }
+
+    #[test]
+    fn renders_json_and_table() {
+        let report = sample();
+
+        let value = serde_json::to_value(&report).unwrap();
+        assert_eq!(value["children"][1]["celo_deposits"], json!("500"));
+        assert_eq!(value["children"][1]["status"], json!("mismatch"));
+        assert_eq!(value["children"][2]["oasis_total_deposited"], json!(null));
+        assert_eq!(value["vault"]["solvent"], json!(false));
+
+        let table = report.to_string();
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(lines[0].starts_with("child "), "{table}");
+        assert!(lines[2].contains("300") && lines[2].ends_with("MISMATCH"), "{table}");
+        assert!(lines[3].ends_with("NO PROFILE"), "{table}");
+        assert!(lines[4].ends_with("UNREADABLE"), "{table}");
+        assert!(table.contains("2 of 4 children disagree with Oasis"), "{table}");
+        assert!(table.contains("1 of 4 children could not be read from Oasis"), "{table}");
+        assert!(table.ends_with("SHORT 250 wei\n"), "{table}");
+    }
+}
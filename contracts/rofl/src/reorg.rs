@@ -0,0 +1,116 @@
+use crate::error::{Error, Result};
+use crate::rpc::RpcClient;
+use alloy_primitives::B256;
+use log::warn;
+use std::collections::BTreeMap;
+
+/// Recent block hashes of one chain, for spotting reorgs
+///
+/// Keeps the hash of every block in the last `depth` blocks up to the head
+/// the indexer has reached. Each newly seen block must name the previous
+/// one as its parent; when that fails, or the tracked tip is no longer on
+/// the node's chain, the tracker walks back to the newest block the node
+/// still agrees on and reports it as the common ancestor. Blocks older than
+/// `depth` are treated as final, and a reorg reaching past them is an error
+/// rather than something to absorb quietly.
+pub struct BlockTracker {
+    depth: u64,
+    hashes: BTreeMap<u64, B256>,
+}
+
+impl BlockTracker {
+    pub fn new(depth: u64) -> Self {
+        Self {
+            depth: depth.max(1),
+            hashes: BTreeMap::new(),
+        }
+    }
+
+    /// Resume with hashes saved by an earlier run
+    pub fn restore(&mut self, hashes: BTreeMap<u64, B256>) {
+        self.hashes = hashes;
+    }
+
+    pub fn hashes(&self) -> &BTreeMap<u64, B256> {
+        &self.hashes
+    }
+
+    /// Tracked hash of block `number`, if it is inside the window
+    pub fn hash(&self, number: u64) -> Option<B256> {
+        self.hashes.get(&number).copied()
+    }
+
+    /// Track blocks up to `head`. Returns the common ancestor if the chain
+    /// reorganized under tracked blocks; hashes above it are dropped, and
+    /// the caller should roll back to it and call again.
+    pub async fn advance(&mut self, rpc: &RpcClient, head: u64) -> Result<Option<u64>> {
+        let tip = self.hashes.last_key_value().map(|(&number, &hash)| (number, hash));
+
+        if let Some((tip, hash)) = tip {
+            if rpc.block_header(tip).await?.map(|header| header.hash) != Some(hash) {
+                return self.rewind(rpc, tip).await.map(Some);
+            }
+        }
+
+        let window_start = head.saturating_sub(self.depth - 1);
+        let start = tip.map_or(window_start, |(tip, _)| window_start.max(tip + 1));
+        for number in start..=head {
+            let header = rpc
+                .block_header(number)
+                .await?
+                .ok_or_else(|| Error::Decode(format!("node has no block {} below its head {}", number, head)))?;
+            let parent = number.checked_sub(1).and_then(|parent| self.hash(parent));
+            if parent.is_some_and(|parent| parent != header.parent_hash) {
+                return self.rewind(rpc, number - 1).await.map(Some);
+            }
+            self.hashes.insert(number, header.hash);
+        }
+
+        // Older blocks are final
+        self.hashes = self.hashes.split_off(&window_start);
+        Ok(None)
+    }
+
+    /// Walk back from `from` to the newest tracked block the node still has,
+    /// dropping everything above it
+    async fn rewind(&mut self, rpc: &RpcClient, from: u64) -> Result<u64> {
+        let tracked: Vec<(u64, B256)> = self.hashes.range(..=from).rev().map(|(&n, &h)| (n, h)).collect();
+        for (number, hash) in tracked {
+            if rpc.block_header(number).await?.map(|header| header.hash) == Some(hash) {
+                warn!("Chain reorganized above block {} ({})", number, hash);
+                self.hashes.split_off(&(number + 1));
+                return Ok(number);
+            }
+        }
+        Err(Error::DeepReorg { depth: self.depth })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc::MockChain;
+
+    #[tokio::test]
+    async fn finds_common_ancestor_and_keeps_window() {
+        let chain = MockChain::start(20).await;
+        let rpc = RpcClient::new(reqwest::Client::new(), chain.url());
+        let mut tracker = BlockTracker::new(8);
+
+        assert_eq!(tracker.advance(&rpc, 19).await.unwrap(), None);
+        assert_eq!(tracker.hashes().keys().copied().collect::<Vec<_>>(), (12..=19).collect::<Vec<_>>());
+
+        // Blocks 16.. are replaced by a longer fork
+        chain.reorg(16, 23);
+        assert_eq!(tracker.advance(&rpc, 23).await.unwrap(), Some(15));
+        assert_eq!(tracker.hashes().keys().last(), Some(&15));
+        assert_eq!(tracker.advance(&rpc, 23).await.unwrap(), None);
+        assert_eq!(tracker.hash(20), Some(chain.hash(20)));
+        assert_eq!(tracker.hashes().len(), 8);
+
+        // A fork below the window cannot be absorbed
+        chain.reorg(10, 23);
+        let err = tracker.advance(&rpc, 23).await.unwrap_err();
+        assert!(matches!(err, Error::DeepReorg { depth: 8 }), "{err}");
+    }
+}
@@ -0,0 +1,115 @@
+use crate::error::Error;
+use rand::Rng;
+use std::time::Duration;
+
+/// How `RpcClient` retries failed requests
+///
+/// Only errors classified as retryable (transport failures, timeouts,
+/// rate limits, transient node errors) are retried. Between attempts the
+/// client sleeps `base_delay * 2^n`, capped at `max_delay`, with up to
+/// `jitter` of each delay randomized away so clients that failed together
+/// do not retry together. A rate-limited response that says how long to
+/// wait (`Retry-After`) is honoured instead, still capped at `max_delay`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Total tries per request, including the first
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Fraction of each delay that is randomized, `0.0..=1.0`
+    pub jitter: f64,
+    /// Timeout for a single attempt
+    pub call_timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(10),
+            jitter: 0.5,
+            call_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// One attempt, no retries
+    pub fn none(call_timeout: Duration) -> Self {
+        Self {
+            max_attempts: 1,
+            call_timeout,
+            ..Self::default()
+        }
+    }
+
+    /// How long to wait before retry number `retry` (1-based) after `error`,
+    /// or `None` if the request should not be retried
+    pub fn delay(&self, retry: u32, error: &Error) -> Option<Duration> {
+        if retry >= self.max_attempts || !error.is_retryable() {
+            return None;
+        }
+        if let Some(wait) = error.retry_after() {
+            return Some(wait.min(self.max_delay));
+        }
+
+        let backoff = self
+            .base_delay
+            .saturating_mul(1 << retry.saturating_sub(1).min(16))
+            .min(self.max_delay);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let keep = 1.0 - jitter * rand::thread_rng().gen::<f64>();
+        Some(backoff.mul_f64(keep))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+
+    fn transient() -> Error {
+        Error::RateLimited { retry_after: None }
+    }
+
+    #[test]
+    fn backs_off_exponentially_within_jitter() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            jitter: 0.5,
+            ..RetryPolicy::default()
+        };
+
+        for (retry, full) in [(1, 100), (2, 200), (3, 400), (4, 800), (5, 1000), (9, 1000)] {
+            let delay = policy.delay(retry, &transient()).unwrap();
+            let full = Duration::from_millis(full);
+            assert!(delay <= full && delay >= full / 2, "retry {retry}: {delay:?}");
+        }
+
+        let exact = RetryPolicy { jitter: 0.0, ..policy };
+        assert_eq!(exact.delay(3, &transient()), Some(Duration::from_millis(400)));
+    }
+
+    #[test]
+    fn stops_after_max_attempts_and_on_permanent_errors() {
+        let policy = RetryPolicy::default();
+        assert!(policy.delay(policy.max_attempts - 1, &transient()).is_some());
+        assert!(policy.delay(policy.max_attempts, &transient()).is_none());
+        assert!(policy.delay(1, &Error::Decode("bad".into())).is_none());
+        assert!(policy.delay(1, &Error::TxFailed(B256::ZERO)).is_none());
+        assert!(RetryPolicy::none(Duration::from_secs(1)).delay(1, &transient()).is_none());
+    }
+
+    #[test]
+    fn honours_retry_after_up_to_max_delay() {
+        let policy = RetryPolicy::default();
+        let limited = |secs| Error::RateLimited {
+            retry_after: Some(Duration::from_secs(secs)),
+        };
+        assert_eq!(policy.delay(1, &limited(3)), Some(Duration::from_secs(3)));
+        assert_eq!(policy.delay(1, &limited(600)), Some(policy.max_delay));
+    }
+}
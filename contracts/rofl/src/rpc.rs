@@ -0,0 +1,697 @@
+use crate::error::{Error, Result};
+use crate::metrics::Metrics;
+use crate::retry::RetryPolicy;
+use alloy_primitives::{keccak256, Address, Bytes, B256, U128, U256, U64};
+use log::{debug, error};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time;
+
+/// Per-attempt timeout for clients without an explicit `RetryPolicy`
+const DEFAULT_CALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Weight of the latest outcome in an endpoint's health score
+const HEALTH_SMOOTHING: f64 = 0.3;
+
+/// Time for half of an endpoint's lost health to come back, so a
+/// preferred endpoint gets traffic again after it recovers
+const HEALTH_HALF_LIFE: Duration = Duration::from_secs(300);
+
+/// Log target for alerts that need a human: endpoints serving different
+/// chain data than each other
+pub const ALERT_TARGET: &str = "rofl::alert";
+
+/// Minimal Ethereum JSON-RPC client
+///
+/// Speaks plain JSON-RPC 2.0 over HTTP with reqwest. Only the handful of
+/// methods the monitor needs are wrapped; anything else goes through `request`.
+/// Requests are tried once unless a `RetryPolicy` is set with `with_retry`.
+///
+/// A client can front several endpoints for the same chain. Each request
+/// goes to the healthiest one and fails over down the ranking on transient
+/// errors; the retry policy only kicks in once every endpoint has failed.
+/// `quorum_call` instead asks all of them and requires agreement.
+pub struct RpcClient {
+    client: Client,
+    endpoints: Vec<Endpoint>,
+    next_id: AtomicU64,
+    retry: RetryPolicy,
+    /// Endpoints that must agree in `quorum_call`
+    quorum: usize,
+    /// Latency and error series, labelled with the chain name
+    metrics: Option<(Arc<Metrics>, &'static str)>,
+}
+
+struct Endpoint {
+    url: String,
+    /// Host only, for logs and metric labels; URLs often carry API keys
+    host: String,
+    health: Mutex<Health>,
+}
+
+/// Exponentially smoothed success rate, 1.0 for a perfect endpoint
+#[derive(Debug, Clone, Copy)]
+struct Health {
+    score: f64,
+    updated: Instant,
+}
+
+impl Health {
+    /// Score now, with lost health partly restored by the time passed
+    fn current(&self, now: Instant) -> f64 {
+        let half_lives = now.saturating_duration_since(self.updated).as_secs_f64() / HEALTH_HALF_LIFE.as_secs_f64();
+        1.0 - (1.0 - self.score) * 0.5f64.powf(half_lives)
+    }
+
+    fn record(&mut self, ok: bool, now: Instant) {
+        let outcome = if ok { 1.0 } else { 0.0 };
+        self.score = self.current(now) * (1.0 - HEALTH_SMOOTHING) + outcome * HEALTH_SMOOTHING;
+        self.updated = now;
+    }
+}
+
+impl Endpoint {
+    fn new(url: String) -> Self {
+        let host = Url::parse(&url)
+            .ok()
+            .and_then(|u| Some(format!("{}:{}", u.host_str()?, u.port_or_known_default()?)))
+            .unwrap_or_else(|| url.clone());
+        Self {
+            url,
+            host,
+            health: Mutex::new(Health {
+                score: 1.0,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    fn score(&self) -> f64 {
+        self.health.lock().unwrap().current(Instant::now())
+    }
+}
+
+/// Error object returned by a JSON-RPC node
+#[derive(Debug, Clone, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: Option<Value>,
+}
+
+impl fmt::Display for JsonRpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JSON-RPC error {}: {}", self.code, self.message)?;
+        if let Some(data) = &self.data {
+            write!(f, " ({})", data)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for JsonRpcError {}
+
+impl JsonRpcError {
+    /// Revert data attached to an `execution reverted` error, if any.
+    /// Nodes put it either directly in `data` or nested as `data.data`.
+    pub fn revert_data(&self) -> Option<Bytes> {
+        let data = self.data.as_ref()?;
+        let hex = data.as_str().or_else(|| data.get("data")?.as_str())?;
+        hex.parse().ok()
+    }
+}
+
+/// A log entry as returned by `eth_getLogs`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Log {
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+    pub block_number: U64,
+    /// Missing on pending logs, like the transaction hash and log index
+    #[serde(default)]
+    pub block_hash: Option<B256>,
+    #[serde(default)]
+    pub transaction_hash: Option<B256>,
+    #[serde(default)]
+    pub log_index: Option<U64>,
+    /// Set on subscription logs dropped by a reorg
+    #[serde(default)]
+    pub removed: bool,
+}
+
+/// Block header fields used for reorg detection and delivery timing
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockHeader {
+    pub hash: B256,
+    pub parent_hash: B256,
+    pub timestamp: U64,
+}
+
+/// Subset of a transaction receipt the monitor looks at
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionReceipt {
+    pub transaction_hash: B256,
+    pub block_number: U64,
+    pub gas_used: U64,
+    pub status: U64,
+}
+
+impl TransactionReceipt {
+    pub fn succeeded(&self) -> bool {
+        self.status == U64::from(1)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcResponse {
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<JsonRpcError>,
+}
+
+impl RpcClient {
+    pub fn new(client: Client, url: impl Into<String>) -> Self {
+        Self::failover(client, [url.into()])
+    }
+
+    /// Client over several endpoints for one chain, in order of preference
+    pub fn failover(client: Client, urls: impl IntoIterator<Item = String>) -> Self {
+        let endpoints: Vec<Endpoint> = urls.into_iter().map(Endpoint::new).collect();
+        assert!(!endpoints.is_empty(), "RpcClient needs at least one endpoint");
+        Self {
+            client,
+            endpoints,
+            next_id: AtomicU64::new(1),
+            retry: RetryPolicy::none(DEFAULT_CALL_TIMEOUT),
+            quorum: 1,
+            metrics: None,
+        }
+    }
+
+    /// Require `quorum` endpoints to agree in `quorum_call`
+    pub fn with_quorum(mut self, quorum: usize) -> Self {
+        self.quorum = quorum.max(1);
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Record every request under `chain` in `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>, chain: &'static str) -> Self {
+        self.metrics = Some((metrics, chain));
+        self
+    }
+
+    /// Host and port of the preferred endpoint, safe to log since URLs can carry API keys
+    pub fn host(&self) -> &str {
+        &self.endpoints[0].host
+    }
+
+    /// Endpoints from healthiest to least healthy; ties keep the configured order
+    fn ranked(&self) -> Vec<&Endpoint> {
+        let mut ranked: Vec<(f64, &Endpoint)> = self.endpoints.iter().map(|e| (e.score(), e)).collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+        ranked.into_iter().map(|(_, endpoint)| endpoint).collect()
+    }
+
+    /// Send a single JSON-RPC request and deserialize its `result`.
+    /// Transient failures fail over to the next endpoint; once all of them
+    /// have failed, the client's `RetryPolicy` decides whether to go again.
+    pub async fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let mut retry = 0;
+        loop {
+            let mut last_error = None;
+            for endpoint in self.ranked() {
+                match self.attempt(endpoint, method, &params).await {
+                    Ok(value) => return Ok(value),
+                    Err(e) if e.is_retryable() => {
+                        debug!("{} {} failed: {}", endpoint.host, method, e);
+                        last_error = Some(e);
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+
+            let error = last_error.expect("every endpoint was tried");
+            retry += 1;
+            match self.retry.delay(retry, &error) {
+                Some(delay) => {
+                    debug!("{} failed on every endpoint ({}), retry {} in {:?}", method, error, retry, delay);
+                    time::sleep(delay).await;
+                }
+                None => return Err(error),
+            }
+        }
+    }
+
+    /// One try against one endpoint, recorded in its health and the metrics.
+    /// Only transient errors count against an endpoint; a revert or a bad
+    /// request is an answer, not an outage.
+    async fn attempt<T: DeserializeOwned>(&self, endpoint: &Endpoint, method: &str, params: &Value) -> Result<T> {
+        let started = Instant::now();
+        let result = self.send_request(&endpoint.url, method, params).await;
+
+        let healthy = result.as_ref().map_or_else(|e| !e.is_retryable(), |_| true);
+        let score = {
+            let mut health = endpoint.health.lock().unwrap();
+            health.record(healthy, Instant::now());
+            health.score
+        };
+
+        if let Some((metrics, chain)) = &self.metrics {
+            metrics
+                .rpc_latency
+                .with_label_values(&[chain, method])
+                .observe(started.elapsed().as_secs_f64());
+            if result.is_err() {
+                metrics.rpc_errors.with_label_values(&[chain, method]).inc();
+            }
+            metrics
+                .rpc_endpoint_health
+                .with_label_values(&[chain, &endpoint.host])
+                .set(score);
+        }
+
+        result
+    }
+
+    async fn send_request<T: DeserializeOwned>(&self, url: &str, method: &str, params: &Value) -> Result<T> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+
+        let response = self
+            .client
+            .post(url)
+            .timeout(self.retry.call_timeout)
+            .json(&body)
+            .send()
+            .await?;
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(Error::RateLimited {
+                retry_after: retry_after(&response),
+            });
+        }
+        let response: JsonRpcResponse = response.error_for_status()?.json().await?;
+
+        if let Some(error) = response.error {
+            return Err(error.into());
+        }
+
+        Ok(serde_json::from_value(response.result.unwrap_or(Value::Null))?)
+    }
+
+    /// `eth_call` against the latest block
+    pub async fn eth_call(&self, to: Address, data: Bytes) -> Result<Bytes> {
+        self.request("eth_call", json!([{ "to": to, "data": data }, "latest"]))
+            .await
+    }
+
+    /// `eth_call` that at least `quorum` endpoints agree on
+    ///
+    /// Every endpoint is asked for its head; the call then runs at the
+    /// highest block that `quorum` of them have reached, and each endpoint
+    /// reports that block's hash along with the result. An answer counts
+    /// only when both match. Any disagreement is logged under
+    /// `ALERT_TARGET`, even when a quorum still agrees.
+    pub async fn quorum_call(&self, to: Address, data: Bytes) -> Result<Bytes> {
+        if self.quorum <= 1 {
+            return self.eth_call(to, data).await;
+        }
+
+        let mut heads = Vec::with_capacity(self.endpoints.len());
+        for endpoint in &self.endpoints {
+            match self.attempt::<U64>(endpoint, "eth_blockNumber", &json!([])).await {
+                Ok(head) => heads.push(head.to::<u64>()),
+                Err(e) => debug!("{} left out of quorum: {}", endpoint.host, e),
+            }
+        }
+        heads.sort_unstable_by(|a, b| b.cmp(a));
+        let Some(&block) = heads.get(self.quorum - 1) else {
+            return Err(Error::NoQuorum {
+                agreeing: heads.len(),
+                required: self.quorum,
+            });
+        };
+
+        let call = json!([{ "to": to, "data": data }, U64::from(block)]);
+        let mut answers: Vec<((B256, Bytes), Vec<&str>)> = Vec::new();
+        let mut call_error = None;
+        for endpoint in &self.endpoints {
+            let answer = async {
+                let header: Option<BlockHeader> =
+                    self.attempt(endpoint, "eth_getBlockByNumber", &json!([U64::from(block), false])).await?;
+                let Some(header) = header else {
+                    return Ok(None);
+                };
+                let result: Bytes = self.attempt(endpoint, "eth_call", &call).await?;
+                Ok::<_, Error>(Some((header.hash, result)))
+            };
+            match answer.await {
+                Ok(Some(answer)) => match answers.iter_mut().find(|(a, _)| *a == answer) {
+                    Some((_, hosts)) => hosts.push(&endpoint.host),
+                    None => answers.push((answer, vec![&endpoint.host])),
+                },
+                // A replica that has not caught up yet abstains, like an unreachable one
+                Ok(None) => debug!("{} left out of quorum: block {} not found", endpoint.host, block),
+                Err(e) => {
+                    debug!("{} left out of quorum: {}", endpoint.host, e);
+                    call_error = Some(e);
+                }
+            }
+        }
+
+        if answers.len() > 1 {
+            let report: Vec<String> = answers
+                .iter()
+                .map(|((hash, result), hosts)| format!("{} say block {} and {}", hosts.join(", "), hash, result))
+                .collect();
+            error!(
+                target: ALERT_TARGET,
+                "RPC endpoints disagree on eth_call to {} at block {}: {}",
+                to,
+                block,
+                report.join("; ")
+            );
+            if let Some((metrics, chain)) = &self.metrics {
+                metrics.rpc_disagreements.with_label_values(&[chain, "eth_call"]).inc();
+            }
+        }
+
+        match answers.into_iter().max_by_key(|(_, hosts)| hosts.len()) {
+            Some(((_, result), hosts)) if hosts.len() >= self.quorum => Ok(result),
+            // All endpoints agreeing it reverts is an answer in itself
+            None => match call_error {
+                Some(e) if !e.is_retryable() => Err(e),
+                _ => Err(Error::NoQuorum {
+                    agreeing: 0,
+                    required: self.quorum,
+                }),
+            },
+            Some((_, hosts)) => Err(Error::NoQuorum {
+                agreeing: hosts.len(),
+                required: self.quorum,
+            }),
+        }
+    }
+
+    /// `eth_call` from `from` at the latest block, for views that check
+    /// `msg.sender`
+    pub async fn eth_call_from(&self, from: Address, to: Address, data: Bytes) -> Result<Bytes> {
+        self.request("eth_call", json!([{ "from": from, "to": to, "data": data }, "latest"]))
+            .await
+    }
+
+    /// `eth_call` from `from` at a specific block, used to replay a mined transaction
+    pub async fn eth_call_at(
+        &self,
+        from: Address,
+        to: Address,
+        data: Bytes,
+        block: u64,
+    ) -> Result<Bytes> {
+        self.request(
+            "eth_call",
+            json!([{ "from": from, "to": to, "data": data }, U64::from(block)]),
+        )
+        .await
+    }
+
+    pub async fn chain_id(&self) -> Result<u64> {
+        let id: U64 = self.request("eth_chainId", json!([])).await?;
+        Ok(id.to())
+    }
+
+    pub async fn gas_price(&self) -> Result<u128> {
+        let price: U128 = self.request("eth_gasPrice", json!([])).await?;
+        Ok(price.to())
+    }
+
+    /// Native balance of `address` at the latest block
+    pub async fn balance(&self, address: Address) -> Result<U256> {
+        self.request("eth_getBalance", json!([address, "latest"])).await
+    }
+
+    /// Deployed bytecode at `address`, empty for accounts without code
+    pub async fn code(&self, address: Address) -> Result<Bytes> {
+        self.request("eth_getCode", json!([address, "latest"])).await
+    }
+
+    /// Number of transactions from `address` mined at the latest block
+    pub async fn nonce(&self, address: Address) -> Result<u64> {
+        let nonce: U64 = self
+            .request("eth_getTransactionCount", json!([address, "latest"]))
+            .await?;
+        Ok(nonce.to())
+    }
+
+    /// Nonce for the next transaction from `address`, counting pending ones
+    pub async fn pending_nonce(&self, address: Address) -> Result<u64> {
+        let nonce: U64 = self
+            .request("eth_getTransactionCount", json!([address, "pending"]))
+            .await?;
+        Ok(nonce.to())
+    }
+
+    /// `eth_estimateGas`; reverts come back as a `JsonRpcError` carrying the revert data
+    pub async fn estimate_gas(
+        &self,
+        from: Address,
+        to: Address,
+        data: Bytes,
+    ) -> Result<u64> {
+        let gas: U64 = self
+            .request("eth_estimateGas", json!([{ "from": from, "to": to, "data": data }]))
+            .await?;
+        Ok(gas.to())
+    }
+
+    /// Broadcast a signed transaction. A retry of a send whose response was
+    /// lost is answered with "already known"; that is success, and the hash
+    /// is the hash of the raw transaction.
+    pub async fn send_raw_transaction(&self, raw: Bytes) -> Result<B256> {
+        match self.request("eth_sendRawTransaction", json!([raw])).await {
+            Err(Error::Rpc(e)) if e.message.contains("already known") => Ok(keccak256(&raw)),
+            result => result,
+        }
+    }
+
+    /// Receipt for `hash`, or `None` while the transaction is still pending
+    pub async fn transaction_receipt(
+        &self,
+        hash: B256,
+    ) -> Result<Option<TransactionReceipt>> {
+        self.request("eth_getTransactionReceipt", json!([hash])).await
+    }
+
+    /// Header of block `number`, or `None` if the node does not have it
+    pub async fn block_header(&self, number: u64) -> Result<Option<BlockHeader>> {
+        self.request("eth_getBlockByNumber", json!([U64::from(number), false])).await
+    }
+
+    /// Latest block number
+    pub async fn block_number(&self) -> Result<u64> {
+        let number: U64 = self.request("eth_blockNumber", json!([])).await?;
+        Ok(number.to())
+    }
+
+    /// `eth_getLogs` for one contract over an inclusive block range,
+    /// matching any of the event signatures in `topic0`
+    pub async fn get_logs(
+        &self,
+        address: Address,
+        topic0: &[B256],
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<Log>> {
+        self.request(
+            "eth_getLogs",
+            json!([{
+                "address": address,
+                "topics": [topic0],
+                "fromBlock": U64::from(from_block),
+                "toBlock": U64::from(to_block),
+            }]),
+        )
+        .await
+    }
+}
+
+/// `Retry-After` in delta-seconds form; HTTP dates are rare from RPC
+/// providers and fall back to the regular backoff
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+    value.trim().parse().ok().map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc::{Fault, MockRpc};
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_secs(5),
+            jitter: 0.0,
+            call_timeout: Duration::from_millis(200),
+        }
+    }
+
+    async fn node() -> MockRpc {
+        MockRpc::start(|method, _| match method {
+            "eth_blockNumber" => Ok(json!("0x10")),
+            "eth_sendRawTransaction" => Err(JsonRpcError {
+                code: -32000,
+                message: "already known".to_string(),
+                data: None,
+            }),
+            _ => Err(JsonRpcError {
+                code: -32601,
+                message: "method not found".to_string(),
+                data: None,
+            }),
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn retries_dropped_and_slow_requests() {
+        let node = node().await;
+        node.inject([Fault::Drop, Fault::Delay(Duration::from_secs(2))]);
+        let rpc = RpcClient::new(Client::new(), node.url()).with_retry(policy(3));
+
+        let started = Instant::now();
+        assert_eq!(rpc.block_number().await.unwrap(), 0x10);
+        assert!(started.elapsed() < Duration::from_secs(2), "slow request was not timed out");
+    }
+
+    #[tokio::test]
+    async fn honours_retry_after_on_429() {
+        let node = node().await;
+        node.inject([Fault::TooManyRequests { retry_after: Some(1) }]);
+        let rpc = RpcClient::new(Client::new(), node.url()).with_retry(policy(2));
+
+        let started = Instant::now();
+        assert_eq!(rpc.block_number().await.unwrap(), 0x10);
+        assert!(started.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts_or_permanent_errors() {
+        let node = node().await;
+        node.inject([Fault::TooManyRequests { retry_after: None }; 3]);
+        let rpc = RpcClient::new(Client::new(), node.url()).with_retry(policy(3));
+
+        let err = rpc.block_number().await.unwrap_err();
+        assert!(matches!(err, Error::RateLimited { retry_after: None }), "{err}");
+        assert!(node.calls().is_empty());
+
+        let err = rpc.request::<Value>("eth_foo", json!([])).await.unwrap_err();
+        assert!(!err.is_retryable());
+        assert_eq!(node.calls().len(), 1, "permanent errors are not retried");
+    }
+
+    #[tokio::test]
+    async fn fails_over_and_prefers_the_healthy_endpoint() {
+        let node = node().await;
+        let rpc = RpcClient::failover(Client::new(), ["http://127.0.0.1:1".to_string(), node.url().to_string()]);
+
+        assert_eq!(rpc.block_number().await.unwrap(), 0x10);
+        assert_eq!(rpc.ranked()[0].url, node.url(), "failed endpoint was not demoted");
+        assert_eq!(rpc.block_number().await.unwrap(), 0x10);
+        assert_eq!(node.calls().len(), 2);
+    }
+
+    /// A node at `head` whose every eth_call returns `result`
+    async fn replica(head: u64, result: &'static str) -> MockRpc {
+        MockRpc::start(move |method, params| match method {
+            "eth_blockNumber" => Ok(json!(U64::from(head))),
+            "eth_getBlockByNumber" => {
+                let number: U64 = serde_json::from_value(params[0].clone()).unwrap();
+                let hash = |n: u64| B256::left_padding_from(&n.to_be_bytes());
+                let n = number.to::<u64>();
+                Ok(json!({ "number": number, "hash": hash(n), "parentHash": hash(n.saturating_sub(1)), "timestamp": number }))
+            }
+            "eth_call" => Ok(json!(result)),
+            other => panic!("unexpected method {other}"),
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn quorum_call_needs_agreement_at_a_common_block() {
+        let nodes = [replica(0x12, "0x01").await, replica(0x11, "0x01").await, replica(0x10, "0x02").await];
+        let metrics = Arc::new(Metrics::new());
+        let rpc = |quorum| {
+            RpcClient::failover(Client::new(), nodes.iter().map(|n| n.url().to_string()))
+                .with_quorum(quorum)
+                .with_metrics(metrics.clone(), "celo")
+        };
+
+        let result = rpc(2).quorum_call(Address::ZERO, Bytes::new()).await.unwrap();
+        assert_eq!(result, Bytes::from_static(&[1]));
+        // Pinned to the highest block two endpoints have
+        for node in &nodes {
+            let (_, params) = node.calls().into_iter().find(|(method, _)| method == "eth_call").unwrap();
+            assert_eq!(params[1], json!("0x11"));
+        }
+        assert_eq!(metrics.rpc_disagreements.with_label_values(&["celo", "eth_call"]).get(), 1);
+
+        let err = rpc(3).quorum_call(Address::ZERO, Bytes::new()).await.unwrap_err();
+        assert!(matches!(err, Error::NoQuorum { agreeing: 2, required: 3 }), "{err}");
+        assert!(err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn quorum_call_leaves_out_lagging_replicas() {
+        // Claims head 0x12 but does not serve the block yet, as a replica
+        // behind a load balancer can
+        let lagging = || {
+            MockRpc::start(|method, _| match method {
+                "eth_blockNumber" => Ok(json!("0x12")),
+                "eth_getBlockByNumber" => Ok(Value::Null),
+                other => panic!("unexpected method {other}"),
+            })
+        };
+        let nodes = [replica(0x12, "0x01").await, replica(0x12, "0x01").await, lagging().await];
+        let rpc = RpcClient::failover(Client::new(), nodes.iter().map(|n| n.url().to_string())).with_quorum(2);
+        let result = rpc.quorum_call(Address::ZERO, Bytes::new()).await.unwrap();
+        assert_eq!(result, Bytes::from_static(&[1]));
+
+        let nodes = [lagging().await, lagging().await];
+        let rpc = RpcClient::failover(Client::new(), nodes.iter().map(|n| n.url().to_string())).with_quorum(2);
+        let err = rpc.quorum_call(Address::ZERO, Bytes::new()).await.unwrap_err();
+        assert!(matches!(err, Error::NoQuorum { agreeing: 0, required: 2 }), "{err}");
+        assert!(err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn resent_transaction_is_already_known() {
+        let node = node().await;
+        let rpc = RpcClient::new(Client::new(), node.url());
+        let raw = Bytes::from_static(&[0x02, 0xf8, 0x6b]);
+
+        assert_eq!(rpc.send_raw_transaction(raw.clone()).await.unwrap(), keccak256(&raw));
+    }
+}
@@ -0,0 +1,297 @@
+use crate::fees::FeeQuoter;
+use crate::health::ReadinessProbe;
+use crate::metrics::Metrics;
+use crate::unix_now;
+use hyper::header::{
+    HeaderValue, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
+    ACCESS_CONTROL_MAX_AGE, CONTENT_TYPE, VARY,
+};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use alloy_primitives::{Address, U256};
+use log::{error, info, warn};
+use serde::Serialize;
+use serde_json::json;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// Operational HTTP endpoints of the monitor
+///
+/// - `GET /metrics`: Prometheus text exposition
+/// - `GET /healthz`: liveness, 200 while the process serves requests
+/// - `GET /readyz`: readiness, 200 or 503 with a JSON body listing failures
+/// - `GET /fees?child=&parent=&amount=`: what to send with a bridged
+///   deposit of `amount` wei, when a bridge is configured. Browsers on the
+///   allowed origin may call it, after an `OPTIONS` preflight.
+pub struct StatusServer {
+    metrics: Arc<Metrics>,
+    readiness: ReadinessProbe,
+    fees: Option<FeeQuoter>,
+    allowed_origin: HeaderValue,
+}
+
+impl StatusServer {
+    pub fn new(metrics: Arc<Metrics>, readiness: ReadinessProbe) -> Self {
+        Self {
+            metrics,
+            readiness,
+            fees: None,
+            allowed_origin: HeaderValue::from_static("*"),
+        }
+    }
+
+    /// Serve bridge fee quotes from `quoter` to browsers on
+    /// `allowed_origin`, an origin or `*`
+    pub fn fees(mut self, quoter: FeeQuoter, allowed_origin: &str) -> Self {
+        self.fees = Some(quoter);
+        // Config only passes `*` or a serialized origin
+        self.allowed_origin = HeaderValue::from_str(allowed_origin).expect("valid origin");
+        self
+    }
+
+    /// Bind `addr` and serve in the background. Returns the bound address
+    /// (useful with port 0); binding errors surface here, not later.
+    pub fn spawn(self, addr: SocketAddr) -> Result<SocketAddr, hyper::Error> {
+        let endpoints = match self.fees {
+            Some(_) => "/metrics, /healthz, /readyz and /fees",
+            None => "/metrics, /healthz and /readyz",
+        };
+        let server = Arc::new(self);
+        let make_service = make_service_fn(move |_| {
+            let server = server.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let server = server.clone();
+                    async move { Ok::<_, Infallible>(server.handle(req).await) }
+                }))
+            }
+        });
+
+        let http = Server::try_bind(&addr)?.serve(make_service);
+        let local_addr = http.local_addr();
+        info!("Serving {} on http://{}", endpoints, local_addr);
+        tokio::spawn(async move {
+            if let Err(e) = http.await {
+                error!("Status server stopped: {}", e);
+            }
+        });
+        Ok(local_addr)
+    }
+
+    async fn handle(&self, req: Request<Body>) -> Response<Body> {
+        match (req.method(), req.uri().path()) {
+            (&Method::GET, "/metrics") => Response::builder()
+                .header(CONTENT_TYPE, prometheus::TEXT_FORMAT)
+                .body(Body::from(self.metrics.render()))
+                .expect("valid response"),
+            (&Method::GET, "/healthz") => json_response(StatusCode::OK, &json!({ "status": "ok" })),
+            (&Method::GET, "/readyz") => {
+                let readiness = self.readiness.check(unix_now()).await;
+                let status = if readiness.ready {
+                    StatusCode::OK
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE
+                };
+                json_response(status, &readiness)
+            }
+            (&Method::OPTIONS, "/fees") if self.fees.is_some() => {
+                let mut response = Response::builder()
+                    .status(StatusCode::NO_CONTENT)
+                    .header(ACCESS_CONTROL_ALLOW_METHODS, "GET, OPTIONS")
+                    .header(ACCESS_CONTROL_ALLOW_HEADERS, "Content-Type")
+                    .header(ACCESS_CONTROL_MAX_AGE, "86400")
+                    .body(Body::empty())
+                    .expect("valid response");
+                self.allow_origin(&mut response);
+                response
+            }
+            (&Method::GET, "/fees") if self.fees.is_some() => {
+                let mut response = self.quote_fees(req.uri().query().unwrap_or_default()).await;
+                self.allow_origin(&mut response);
+                response
+            }
+            _ => Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Body::from("not found\n"))
+                .expect("valid response"),
+        }
+    }
+
+    async fn quote_fees(&self, query: &str) -> Response<Body> {
+        let quoter = self.fees.as_ref().expect("only routed with a quoter");
+        let (child, parent, amount) = match fee_request(query) {
+            Ok(request) => request,
+            Err(e) => return json_response(StatusCode::BAD_REQUEST, &json!({ "error": e })),
+        };
+        match quoter.quote(child, parent, amount, unix_now()).await {
+            Ok(quote) => json_response(StatusCode::OK, &quote),
+            // The error can name the Base endpoint, API key and all
+            Err(e) => {
+                warn!("Failed to quote bridge fees for {}: {}", child, e);
+                json_response(
+                    StatusCode::BAD_GATEWAY,
+                    &json!({ "error": "could not read the bridge fee from Base" }),
+                )
+            }
+        }
+    }
+
+    fn allow_origin(&self, response: &mut Response<Body>) {
+        let headers = response.headers_mut();
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, self.allowed_origin.clone());
+        if self.allowed_origin != "*" {
+            headers.insert(VARY, HeaderValue::from_static("Origin"));
+        }
+    }
+}
+
+/// `child`, `parent` and `amount` (wei, decimal) from a `/fees` query
+fn fee_request(query: &str) -> Result<(Address, Address, U256), String> {
+    let param = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+            .ok_or_else(|| format!("missing query parameter {}", name))
+    };
+    let address = |name: &str| {
+        let value = param(name)?;
+        value
+            .parse::<Address>()
+            .map_err(|_| format!("{}: expected an address, got {:?}", name, value))
+    };
+    let amount = param("amount")?;
+    let amount = U256::from_str_radix(amount, 10)
+        .map_err(|_| format!("amount: expected a decimal amount of wei, got {:?}", amount))?;
+    Ok((address("child")?, address("parent")?, amount))
+}
+
+fn json_response(status: StatusCode, body: &impl Serialize) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(body).expect("serializable body")))
+        .expect("valid response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::mock_rpc::MockRpc;
+    use crate::rpc::RpcClient;
+    use alloy_primitives::Bytes;
+    use alloy_sol_types::SolValue;
+    use serde_json::Value;
+    use std::time::Duration;
+
+    fn spawn(metrics: Arc<Metrics>) -> SocketAddr {
+        let readiness = ReadinessProbe::new(metrics.clone(), Duration::from_secs(120), U256::ZERO);
+        StatusServer::new(metrics, readiness).spawn(([127, 0, 0, 1], 0).into()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn serves_metrics() {
+        let metrics = Arc::new(Metrics::new());
+        metrics.vaults.set(3);
+
+        let addr = spawn(metrics);
+
+        let client = reqwest::Client::new();
+        let response = client.get(format!("http://{}/metrics", addr)).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert!(response.text().await.unwrap().contains("scholarfi_vaults 3"));
+
+        let missing = client.get(format!("http://{}/nope", addr)).send().await.unwrap();
+        assert_eq!(missing.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn readiness_follows_last_cycle() {
+        let metrics = Arc::new(Metrics::new());
+        let addr = spawn(metrics.clone());
+        let client = reqwest::Client::new();
+
+        assert_eq!(client.get(format!("http://{}/healthz", addr)).send().await.unwrap().status(), 200);
+
+        let response = client.get(format!("http://{}/readyz", addr)).send().await.unwrap();
+        assert_eq!(response.status(), 503);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["ready"], false);
+        assert_eq!(body["failures"][0], "no successful monitoring cycle yet");
+
+        metrics.last_success.set(unix_now() as i64);
+        let response = client.get(format!("http://{}/readyz", addr)).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.json::<Value>().await.unwrap(), json!({ "ready": true, "failures": [] }));
+    }
+
+    #[tokio::test]
+    async fn quotes_bridge_fees() {
+        let node = MockRpc::start(|_, _| Ok(json!(Bytes::from(U256::from(2_000u64).abi_encode())))).await;
+        let metrics = Arc::new(Metrics::new());
+        let readiness = ReadinessProbe::new(metrics.clone(), Duration::from_secs(120), U256::ZERO);
+        let quoter = FeeQuoter::new(
+            Address::repeat_byte(0xb1),
+            RpcClient::new(reqwest::Client::new(), node.url()),
+            25,
+            Duration::from_secs(30),
+        );
+        let addr = StatusServer::new(metrics.clone(), readiness)
+            .fees(quoter, "https://app.example")
+            .spawn(([127, 0, 0, 1], 0).into())
+            .unwrap();
+        let client = reqwest::Client::new();
+        let child = Address::repeat_byte(0x01);
+        let parent = Address::repeat_byte(0x02);
+
+        let url = format!("http://{}/fees?child={}&parent={}&amount=10000", addr, child, parent);
+        let response = client.get(url).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["access-control-allow-origin"], "https://app.example");
+        assert_eq!(response.headers()["vary"], "Origin");
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["hyperlane_fee"], "2000");
+        assert_eq!(body["margin"], "500");
+        assert_eq!(body["total"], "12500");
+
+        let url = format!("http://{}/fees?child={}&amount=1", addr, child);
+        let response = client.get(url).send().await.unwrap();
+        assert_eq!(response.status(), 400);
+        assert_eq!(response.headers()["access-control-allow-origin"], "https://app.example");
+        assert_eq!(response.json::<Value>().await.unwrap()["error"], "missing query parameter parent");
+
+        let preflight = client
+            .request(reqwest::Method::OPTIONS, format!("http://{}/fees", addr))
+            .header("Origin", "https://app.example")
+            .header("Access-Control-Request-Method", "GET")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(preflight.status(), 204);
+        assert_eq!(preflight.headers()["access-control-allow-origin"], "https://app.example");
+        assert_eq!(preflight.headers()["access-control-allow-methods"], "GET, OPTIONS");
+
+        // Nothing listens here; the endpoint, key and all, stays out of the response
+        let quoter = FeeQuoter::new(
+            Address::repeat_byte(0xb1),
+            RpcClient::new(reqwest::Client::new(), "http://127.0.0.1:1/v2/secret-key"),
+            25,
+            Duration::from_secs(30),
+        );
+        let readiness = ReadinessProbe::new(metrics.clone(), Duration::from_secs(120), U256::ZERO);
+        let addr = StatusServer::new(metrics, readiness)
+            .fees(quoter, "*")
+            .spawn(([127, 0, 0, 1], 0).into())
+            .unwrap();
+        let url = format!("http://{}/fees?child={}&parent={}&amount=1", addr, child, parent);
+        let response = client.get(url).send().await.unwrap();
+        assert_eq!(response.status(), 502);
+        assert_eq!(response.headers()["access-control-allow-origin"], "*");
+        assert!(!response.headers().contains_key("vary"));
+        let body = response.text().await.unwrap();
+        assert_eq!(body, r#"{"error":"could not read the bridge fee from Base"}"#);
+    }
+}
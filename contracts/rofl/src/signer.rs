@@ -0,0 +1,125 @@
+use crate::error::{Error, Result};
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use alloy_rlp::{BufMut, Encodable, Header};
+use k256::ecdsa::SigningKey;
+
+/// Legacy (type 0) transaction with EIP-155 replay protection
+///
+/// Both Sapphire and Celo accept legacy transactions, which keeps signing
+/// down to one RLP shape on every chain the monitor writes to.
+#[derive(Debug, Clone)]
+pub struct LegacyTransaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub gas_price: u128,
+    pub gas_limit: u64,
+    pub to: Address,
+    pub value: U256,
+    pub data: Bytes,
+}
+
+impl LegacyTransaction {
+    fn fields_len(&self) -> usize {
+        self.nonce.length()
+            + self.gas_price.length()
+            + self.gas_limit.length()
+            + self.to.length()
+            + self.value.length()
+            + self.data.length()
+    }
+
+    fn encode_fields(&self, out: &mut dyn BufMut) {
+        self.nonce.encode(out);
+        self.gas_price.encode(out);
+        self.gas_limit.encode(out);
+        self.to.encode(out);
+        self.value.encode(out);
+        self.data.encode(out);
+    }
+
+    /// EIP-155 signing hash: keccak(rlp([nonce, gasPrice, gas, to, value, data, chainId, 0, 0]))
+    fn signing_hash(&self) -> B256 {
+        let payload_length = self.fields_len() + self.chain_id.length() + 2;
+        let mut out = Vec::with_capacity(payload_length + 4);
+        Header { list: true, payload_length }.encode(&mut out);
+        self.encode_fields(&mut out);
+        self.chain_id.encode(&mut out);
+        0u8.encode(&mut out);
+        0u8.encode(&mut out);
+        keccak256(out)
+    }
+}
+
+/// Local secp256k1 key used by the monitor to sign transactions
+pub struct Signer {
+    key: SigningKey,
+    address: Address,
+}
+
+impl Signer {
+    /// Parse a hex private key, with or without `0x`
+    pub fn from_hex(private_key: &str) -> Result<Self> {
+        let bytes: B256 = private_key
+            .trim()
+            .parse()
+            .map_err(|_| Error::Signer("private key is not 32 bytes of hex".to_string()))?;
+        let key = SigningKey::from_bytes(&bytes.0.into())
+            .map_err(|_| Error::Signer("private key is not a valid secp256k1 scalar".to_string()))?;
+        let public = key.verifying_key().to_encoded_point(false);
+        let address = Address::from_raw_public_key(&public.as_bytes()[1..]);
+        Ok(Self { key, address })
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Sign `tx` and return the raw bytes for `eth_sendRawTransaction`
+    pub fn sign_transaction(&self, tx: &LegacyTransaction) -> Result<Bytes> {
+        let hash = tx.signing_hash();
+        let (signature, recovery_id) = self
+            .key
+            .sign_prehash_recoverable(hash.as_slice())
+            .map_err(|e| Error::Signer(e.to_string()))?;
+
+        let v = tx.chain_id * 2 + 35 + u64::from(recovery_id.is_y_odd());
+        let r = U256::from_be_slice(&signature.r().to_bytes());
+        let s = U256::from_be_slice(&signature.s().to_bytes());
+
+        let payload_length = tx.fields_len() + v.length() + r.length() + s.length();
+        let mut out = Vec::with_capacity(payload_length + 4);
+        Header { list: true, payload_length }.encode(&mut out);
+        tx.encode_fields(&mut out);
+        v.encode(&mut out);
+        r.encode(&mut out);
+        s.encode(&mut out);
+        Ok(out.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, bytes};
+
+    #[test]
+    fn signs_eip155_example_transaction() {
+        // Example from EIP-155
+        let signer = Signer::from_hex("0x4646464646464646464646464646464646464646464646464646464646464646").unwrap();
+        let tx = LegacyTransaction {
+            chain_id: 1,
+            nonce: 9,
+            gas_price: 20_000_000_000,
+            gas_limit: 21_000,
+            to: address!("3535353535353535353535353535353535353535"),
+            value: U256::from(1_000_000_000_000_000_000u128),
+            data: Bytes::new(),
+        };
+
+        assert_eq!(signer.address(), address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F"));
+        assert_eq!(
+            signer.sign_transaction(&tx).unwrap(),
+            bytes!("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83")
+        );
+    }
+}
@@ -0,0 +1,518 @@
+use crate::deposits::{Deposit, DepositKey};
+use crate::error::{Error, Result};
+use crate::hyperlane::{Delivery, Sighting};
+use crate::ledger::LedgerEntry;
+use crate::message::HyperlaneMessage;
+use crate::tx::PendingTx;
+use crate::verification::Verification;
+use alloy_primitives::{hex, Address, B256};
+use log::info;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Schema migrations, applied in order. `PRAGMA user_version` records how
+/// many have run; append new entries, never edit existing ones.
+const MIGRATIONS: &[&str] = &[
+    // 1: initial schema
+    "CREATE TABLE checkpoints (
+        chain TEXT PRIMARY KEY,
+        next_block INTEGER NOT NULL
+    );
+    CREATE TABLE children (
+        address TEXT PRIMARY KEY
+    );
+    CREATE TABLE growth (
+        child TEXT PRIMARY KEY,
+        total_growth TEXT NOT NULL,
+        last_accrued INTEGER NOT NULL
+    );
+    CREATE TABLE pending_txs (
+        hash TEXT PRIMARY KEY,
+        chain_id INTEGER NOT NULL,
+        to_address TEXT NOT NULL,
+        nonce INTEGER NOT NULL,
+        submitted_at INTEGER NOT NULL
+    );",
+    // 2: reorg tracking. Children indexed before this have no recorded
+    // block and are never rolled back.
+    "ALTER TABLE children ADD COLUMN block INTEGER NOT NULL DEFAULT 0;
+    CREATE TABLE block_hashes (
+        chain TEXT NOT NULL,
+        number INTEGER NOT NULL,
+        hash TEXT NOT NULL,
+        PRIMARY KEY (chain, number)
+    );",
+    // 3: deposits mirrored to ChildDataStore.recordDeposit
+    "CREATE TABLE deposits (
+        chain TEXT NOT NULL,
+        tx_hash TEXT NOT NULL,
+        log_index INTEGER NOT NULL,
+        child TEXT NOT NULL,
+        amount TEXT NOT NULL,
+        block INTEGER NOT NULL,
+        recorded_tx TEXT,
+        PRIMARY KEY (chain, tx_hash, log_index)
+    );",
+    // 4: age verification propagated with markAgeVerified
+    "CREATE TABLE verifications (
+        child TEXT PRIMARY KEY,
+        block INTEGER NOT NULL,
+        marked_tx TEXT
+    );",
+    // 5: Hyperlane messages from ScholarFiBridge to the vault
+    "CREATE TABLE deliveries (
+        message_id TEXT PRIMARY KEY,
+        dispatch_block INTEGER,
+        dispatched_at INTEGER,
+        delivery_block INTEGER,
+        delivered_at INTEGER,
+        alerted INTEGER NOT NULL
+    );",
+    // 6: child decoded from the dispatched message body
+    "ALTER TABLE deliveries ADD COLUMN child TEXT;",
+    // 7: packed dispatched message, to simulate its delivery with
+    "ALTER TABLE deliveries ADD COLUMN message TEXT;",
+];
+
+/// Everything the monitor persists between cycles and restarts
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MonitorState {
+    /// Next block to index, per chain
+    pub checkpoints: BTreeMap<String, u64>,
+    /// Known children and the block each was created at
+    pub children: BTreeMap<Address, u64>,
+    /// Recent block hashes per chain, for reorg detection
+    pub block_hashes: BTreeMap<String, BTreeMap<u64, B256>>,
+    /// Last growth written per child
+    pub growth: BTreeMap<Address, LedgerEntry>,
+    /// Deposit events seen on Celo and Base, and their Oasis transactions
+    pub deposits: BTreeMap<DepositKey, Deposit>,
+    /// Children seen age-verified on Celo
+    pub verifications: BTreeMap<Address, Verification>,
+    /// Bridged deposits' Hyperlane messages, by message ID
+    pub deliveries: BTreeMap<B256, Delivery>,
+    pub pending_txs: Vec<PendingTx>,
+}
+
+/// Embedded SQLite store for `MonitorState`
+///
+/// The monitor loads the state once at startup and commits a full snapshot
+/// at the end of every cycle in a single transaction, so a crash leaves the
+/// store at the end of the last completed cycle, never halfway through one.
+pub struct StateStore {
+    conn: Connection,
+}
+
+impl StateStore {
+    /// Open (or create) the store at `path` and bring its schema up to date
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let mut conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        migrate(&mut conn)?;
+        Ok(Self { conn })
+    }
+
+    pub fn load(&self) -> Result<MonitorState> {
+        let mut state = MonitorState::default();
+
+        let mut stmt = self.conn.prepare("SELECT chain, next_block FROM checkpoints")?;
+        for row in stmt.query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?)))? {
+            let (chain, next_block) = row?;
+            state.checkpoints.insert(chain, next_block as u64);
+        }
+
+        let mut stmt = self.conn.prepare("SELECT address, block FROM children")?;
+        for row in stmt.query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?)))? {
+            let (address, block) = row?;
+            state.children.insert(address.parse()?, block as u64);
+        }
+
+        let mut stmt = self.conn.prepare("SELECT chain, number, hash FROM block_hashes")?;
+        let rows = stmt.query_map([], |r| {
+            Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?, r.get::<_, String>(2)?))
+        })?;
+        for row in rows {
+            let (chain, number, hash) = row?;
+            state.block_hashes.entry(chain).or_default().insert(number as u64, hash.parse()?);
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT child, total_growth, last_accrued FROM growth")?;
+        let rows = stmt.query_map([], |r| {
+            Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?, r.get::<_, i64>(2)?))
+        })?;
+        for row in rows {
+            let (child, total_growth, last_accrued) = row?;
+            state.growth.insert(
+                child.parse()?,
+                LedgerEntry {
+                    total_growth: total_growth.parse()?,
+                    last_accrued: last_accrued as u64,
+                },
+            );
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT chain, tx_hash, log_index, child, amount, block, recorded_tx FROM deposits")?;
+        let rows = stmt.query_map([], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, String>(1)?,
+                r.get::<_, i64>(2)?,
+                r.get::<_, String>(3)?,
+                r.get::<_, String>(4)?,
+                r.get::<_, i64>(5)?,
+                r.get::<_, Option<String>>(6)?,
+            ))
+        })?;
+        for row in rows {
+            let (chain, tx_hash, log_index, child, amount, block, recorded_tx) = row?;
+            state.deposits.insert(
+                DepositKey {
+                    chain,
+                    tx_hash: tx_hash.parse()?,
+                    log_index: log_index as u64,
+                },
+                Deposit {
+                    child: child.parse()?,
+                    amount: amount.parse().map_err(|e| Error::Decode(format!("deposit amount: {}", e)))?,
+                    block: block as u64,
+                    recorded_tx: recorded_tx.map(|hash| hash.parse()).transpose()?,
+                },
+            );
+        }
+
+        let mut stmt = self.conn.prepare("SELECT child, block, marked_tx FROM verifications")?;
+        let rows = stmt.query_map([], |r| {
+            Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?, r.get::<_, Option<String>>(2)?))
+        })?;
+        for row in rows {
+            let (child, block, marked_tx) = row?;
+            state.verifications.insert(
+                child.parse()?,
+                Verification {
+                    block: block as u64,
+                    marked_tx: marked_tx.map(|hash| hash.parse()).transpose()?,
+                },
+            );
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT message_id, child, dispatch_block, dispatched_at, delivery_block, delivered_at, alerted, message
+             FROM deliveries",
+        )?;
+        let rows = stmt.query_map([], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, Option<String>>(1)?,
+                r.get::<_, Option<i64>>(2)?,
+                r.get::<_, Option<i64>>(3)?,
+                r.get::<_, Option<i64>>(4)?,
+                r.get::<_, Option<i64>>(5)?,
+                r.get::<_, bool>(6)?,
+                r.get::<_, Option<String>>(7)?,
+            ))
+        })?;
+        for row in rows {
+            let (message_id, child, dispatch_block, dispatched_at, delivery_block, delivered_at, alerted, message) = row?;
+            let sighting = |block: Option<i64>, timestamp: Option<i64>| {
+                Some(Sighting {
+                    block: block? as u64,
+                    timestamp: timestamp? as u64,
+                })
+            };
+            state.deliveries.insert(
+                message_id.parse()?,
+                Delivery {
+                    child: child.map(|child| child.parse()).transpose()?,
+                    message: message
+                        .map(|message| HyperlaneMessage::decode(&hex::decode(message)?))
+                        .transpose()?,
+                    dispatched: sighting(dispatch_block, dispatched_at),
+                    delivered: sighting(delivery_block, delivered_at),
+                    alerted,
+                },
+            );
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT hash, chain_id, to_address, nonce, submitted_at FROM pending_txs")?;
+        let rows = stmt.query_map([], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, i64>(1)?,
+                r.get::<_, String>(2)?,
+                r.get::<_, i64>(3)?,
+                r.get::<_, i64>(4)?,
+            ))
+        })?;
+        for row in rows {
+            let (hash, chain_id, to, nonce, submitted_at) = row?;
+            state.pending_txs.push(PendingTx {
+                hash: hash.parse::<B256>()?,
+                chain_id: chain_id as u64,
+                to: to.parse()?,
+                nonce: nonce as u64,
+                submitted_at: submitted_at as u64,
+            });
+        }
+
+        Ok(state)
+    }
+
+    /// Replace the stored state with `state` atomically
+    pub fn commit(&mut self, state: &MonitorState) -> Result<()> {
+        let tx = self.conn.transaction()?;
+
+        tx.execute("DELETE FROM checkpoints", [])?;
+        for (chain, next_block) in &state.checkpoints {
+            tx.execute(
+                "INSERT INTO checkpoints (chain, next_block) VALUES (?1, ?2)",
+                params![chain, *next_block as i64],
+            )?;
+        }
+
+        tx.execute("DELETE FROM children", [])?;
+        for (child, block) in &state.children {
+            tx.execute(
+                "INSERT INTO children (address, block) VALUES (?1, ?2)",
+                params![child.to_string(), *block as i64],
+            )?;
+        }
+
+        tx.execute("DELETE FROM block_hashes", [])?;
+        for (chain, hashes) in &state.block_hashes {
+            for (number, hash) in hashes {
+                tx.execute(
+                    "INSERT INTO block_hashes (chain, number, hash) VALUES (?1, ?2, ?3)",
+                    params![chain, *number as i64, hash.to_string()],
+                )?;
+            }
+        }
+
+        // u128 does not fit SQLite's INTEGER, so growth is stored as decimal text
+        tx.execute("DELETE FROM growth", [])?;
+        for (child, entry) in &state.growth {
+            tx.execute(
+                "INSERT INTO growth (child, total_growth, last_accrued) VALUES (?1, ?2, ?3)",
+                params![child.to_string(), entry.total_growth.to_string(), entry.last_accrued as i64],
+            )?;
+        }
+
+        tx.execute("DELETE FROM deposits", [])?;
+        for (key, deposit) in &state.deposits {
+            tx.execute(
+                "INSERT INTO deposits (chain, tx_hash, log_index, child, amount, block, recorded_tx)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    key.chain,
+                    key.tx_hash.to_string(),
+                    key.log_index as i64,
+                    deposit.child.to_string(),
+                    deposit.amount.to_string(),
+                    deposit.block as i64,
+                    deposit.recorded_tx.map(|hash| hash.to_string()),
+                ],
+            )?;
+        }
+
+        tx.execute("DELETE FROM verifications", [])?;
+        for (child, verification) in &state.verifications {
+            tx.execute(
+                "INSERT INTO verifications (child, block, marked_tx) VALUES (?1, ?2, ?3)",
+                params![
+                    child.to_string(),
+                    verification.block as i64,
+                    verification.marked_tx.map(|hash| hash.to_string()),
+                ],
+            )?;
+        }
+
+        tx.execute("DELETE FROM deliveries", [])?;
+        for (message_id, delivery) in &state.deliveries {
+            tx.execute(
+                "INSERT INTO deliveries
+                 (message_id, child, dispatch_block, dispatched_at, delivery_block, delivered_at, alerted, message)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    message_id.to_string(),
+                    delivery.child.map(|child| child.to_string()),
+                    delivery.dispatched.map(|seen| seen.block as i64),
+                    delivery.dispatched.map(|seen| seen.timestamp as i64),
+                    delivery.delivered.map(|seen| seen.block as i64),
+                    delivery.delivered.map(|seen| seen.timestamp as i64),
+                    delivery.alerted,
+                    delivery.message.as_ref().map(|message| hex::encode(message.encode())),
+                ],
+            )?;
+        }
+
+        tx.execute("DELETE FROM pending_txs", [])?;
+        for pending in &state.pending_txs {
+            tx.execute(
+                "INSERT INTO pending_txs (hash, chain_id, to_address, nonce, submitted_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    pending.hash.to_string(),
+                    pending.chain_id as i64,
+                    pending.to.to_string(),
+                    pending.nonce as i64,
+                    pending.submitted_at as i64,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+fn migrate(conn: &mut Connection) -> Result<()> {
+    let version: usize = conn
+        .query_row("PRAGMA user_version", [], |r| r.get::<_, i64>(0))
+        .optional()?
+        .unwrap_or(0) as usize;
+
+    if version > MIGRATIONS.len() {
+        return Err(Error::Config(format!(
+            "state store schema v{} is newer than this monitor (v{})",
+            version,
+            MIGRATIONS.len()
+        )));
+    }
+
+    for (index, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.pragma_update(None, "user_version", (index + 1) as i64)?;
+        tx.commit()?;
+        info!("Applied state store migration v{}", index + 1);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, U256};
+
+    fn sample_state() -> MonitorState {
+        let child = address!("0000000000000000000000000000000000000001");
+        MonitorState {
+            checkpoints: BTreeMap::from([("celo".to_string(), 1_234)]),
+            children: BTreeMap::from([(child, 1_200)]),
+            block_hashes: BTreeMap::from([(
+                "celo".to_string(),
+                BTreeMap::from([(1_232, B256::repeat_byte(0x01)), (1_233, B256::repeat_byte(0x02))]),
+            )]),
+            growth: BTreeMap::from([(
+                child,
+                LedgerEntry {
+                    total_growth: u128::MAX,
+                    last_accrued: 1_700_000_000,
+                },
+            )]),
+            deposits: BTreeMap::from([(
+                DepositKey {
+                    chain: "base".to_string(),
+                    tx_hash: B256::repeat_byte(0xde),
+                    log_index: 3,
+                },
+                Deposit {
+                    child,
+                    amount: U256::MAX,
+                    block: 1_100,
+                    recorded_tx: Some(B256::repeat_byte(0xef)),
+                },
+            )]),
+            verifications: BTreeMap::from([(
+                child,
+                Verification {
+                    block: 1_150,
+                    marked_tx: None,
+                },
+            )]),
+            deliveries: BTreeMap::from([(
+                B256::repeat_byte(0x11),
+                Delivery {
+                    child: Some(child),
+                    message: Some(HyperlaneMessage {
+                        version: 3,
+                        nonce: 5,
+                        origin: 84532,
+                        sender: B256::repeat_byte(0x22),
+                        destination: 11142220,
+                        recipient: B256::repeat_byte(0x33),
+                        body: vec![0xca, 0xfe].into(),
+                    }),
+                    dispatched: Some(Sighting {
+                        block: 900,
+                        timestamp: 1_700_000_050,
+                    }),
+                    delivered: None,
+                    alerted: true,
+                },
+            )]),
+            pending_txs: vec![PendingTx {
+                hash: B256::repeat_byte(0xab),
+                chain_id: 23295,
+                to: address!("00000000000000000000000000000000000000cc"),
+                nonce: 7,
+                submitted_at: 1_700_000_100,
+            }],
+        }
+    }
+
+    #[test]
+    fn fresh_store_is_migrated_and_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = StateStore::open(dir.path().join("state.db")).unwrap();
+
+        let version: i64 = store.conn.query_row("PRAGMA user_version", [], |r| r.get(0)).unwrap();
+        assert_eq!(version as usize, MIGRATIONS.len());
+        assert_eq!(store.load().unwrap(), MonitorState::default());
+    }
+
+    #[test]
+    fn commit_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.db");
+
+        let mut store = StateStore::open(&path).unwrap();
+        store.commit(&sample_state()).unwrap();
+        drop(store);
+
+        let store = StateStore::open(&path).unwrap();
+        assert_eq!(store.load().unwrap(), sample_state());
+    }
+
+    #[test]
+    fn commit_replaces_previous_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = StateStore::open(dir.path().join("state.db")).unwrap();
+
+        store.commit(&sample_state()).unwrap();
+        let mut next = sample_state();
+        next.checkpoints.insert("celo".to_string(), 2_000);
+        next.pending_txs.clear();
+        store.commit(&next).unwrap();
+
+        assert_eq!(store.load().unwrap(), next);
+    }
+
+    #[test]
+    fn refuses_newer_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.pragma_update(None, "user_version", 99).unwrap();
+        drop(conn);
+
+        assert!(StateStore::open(&path).is_err());
+    }
+}
@@ -0,0 +1,337 @@
+use crate::contracts::ScholarFiVault::{AgeVerificationCompleted, FundsDeposited, VaultUnlocked, WhitelistPayment};
+use crate::error::{Error, Result};
+use crate::rpc::{JsonRpcError, Log, RpcClient};
+use alloy_primitives::{Address, B256};
+use alloy_sol_types::SolEvent;
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, info, warn};
+use reqwest::Url;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::time;
+use tokio_tungstenite::tungstenite::Message;
+
+/// First reconnect delay, doubling up to `RECONNECT_MAX_DELAY`. A
+/// connection that stayed up for `RECONNECT_MAX_DELAY` resets it.
+const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Events buffered between the socket and the monitor
+const EVENT_BUFFER: usize = 1024;
+
+/// ScholarFiVault events that change a child's balances or status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultEventKind {
+    Deposit,
+    Unlock,
+    Payment,
+    AgeVerified,
+}
+
+impl VaultEventKind {
+    const ALL: [Self; 4] = [Self::Deposit, Self::Unlock, Self::Payment, Self::AgeVerified];
+
+    fn signature(self) -> B256 {
+        match self {
+            Self::Deposit => FundsDeposited::SIGNATURE_HASH,
+            Self::Unlock => VaultUnlocked::SIGNATURE_HASH,
+            Self::Payment => WhitelistPayment::SIGNATURE_HASH,
+            Self::AgeVerified => AgeVerificationCompleted::SIGNATURE_HASH,
+        }
+    }
+
+    /// Metric label
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Deposit => "deposit",
+            Self::Unlock => "unlock",
+            Self::Payment => "payment",
+            Self::AgeVerified => "age_verified",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VaultEvent {
+    pub kind: VaultEventKind,
+    pub child: Address,
+    pub block: u64,
+}
+
+impl VaultEvent {
+    /// Every tracked event has the child as its first indexed parameter,
+    /// so the data section never needs decoding
+    fn from_log(log: &Log) -> Option<Self> {
+        let topic0 = log.topics.first()?;
+        let kind = VaultEventKind::ALL.into_iter().find(|kind| kind.signature() == *topic0)?;
+        Some(Self {
+            kind,
+            child: Address::from_word(*log.topics.get(1)?),
+            block: log.block_number.to(),
+        })
+    }
+}
+
+/// Live ScholarFiVault events over `eth_subscribe("logs")`
+///
+/// Runs in the background and reconnects with backoff whenever the socket
+/// drops. After each reconnect the blocks since the last event seen (or
+/// since the previous connection came up) are backfilled with `eth_getLogs`
+/// over HTTP, so a dropped connection delays events but never loses them.
+/// Events may be delivered twice around a reconnect; handling a child is
+/// idempotent, so that is harmless.
+pub struct VaultSubscription {
+    ws_url: String,
+    /// Host only, for logs; URLs often carry API keys
+    host: String,
+    vault: Address,
+    rpc: RpcClient,
+    page_size: u64,
+}
+
+impl VaultSubscription {
+    pub fn new(ws_url: impl Into<String>, vault: Address, rpc: RpcClient, page_size: u64) -> Self {
+        let ws_url = ws_url.into();
+        let host = Url::parse(&ws_url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| ws_url.clone());
+        Self {
+            ws_url,
+            host,
+            vault,
+            rpc,
+            page_size: page_size.max(1),
+        }
+    }
+
+    /// Start the subscription; it runs until the receiver is dropped
+    pub fn spawn(self) -> mpsc::Receiver<VaultEvent> {
+        let (sender, receiver) = mpsc::channel(EVENT_BUFFER);
+        tokio::spawn(self.run(sender));
+        receiver
+    }
+
+    async fn run(self, events: mpsc::Sender<VaultEvent>) {
+        // Last block whose events have all been delivered
+        let mut synced = None;
+        let mut delay = RECONNECT_BASE_DELAY;
+
+        while !events.is_closed() {
+            let connected = Instant::now();
+            match self.subscribe(&mut synced, &events).await {
+                Ok(()) => info!("Vault subscription on {} closed", self.host),
+                Err(e) => warn!("Vault subscription on {} failed: {}", self.host, e),
+            }
+            if connected.elapsed() >= RECONNECT_MAX_DELAY {
+                delay = RECONNECT_BASE_DELAY;
+            }
+            if events.is_closed() {
+                break;
+            }
+            info!("Reconnecting vault subscription in {}s", delay.as_secs());
+            time::sleep(delay).await;
+            delay = (delay * 2).min(RECONNECT_MAX_DELAY);
+        }
+    }
+
+    /// One connection: subscribe, backfill the gap, then stream until the
+    /// socket closes
+    async fn subscribe(&self, synced: &mut Option<u64>, events: &mpsc::Sender<VaultEvent>) -> Result<()> {
+        let (mut socket, _) = tokio_tungstenite::connect_async(self.ws_url.as_str()).await?;
+        let topics: Vec<B256> = VaultEventKind::ALL.iter().map(|kind| kind.signature()).collect();
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_subscribe",
+            "params": ["logs", { "address": self.vault, "topics": [topics] }],
+        });
+        socket.send(Message::Text(request.to_string())).await?;
+
+        // Nothing is pushed before the subscription is confirmed
+        let subscription = loop {
+            let Some(message) = socket.next().await else {
+                return Ok(());
+            };
+            let Message::Text(text) = message? else {
+                continue;
+            };
+            let reply: Value = serde_json::from_str(&text)?;
+            if reply["id"] != json!(1) {
+                continue;
+            }
+            if let Some(error) = reply.get("error") {
+                return Err(serde_json::from_value::<JsonRpcError>(error.clone())?.into());
+            }
+            break reply["result"].clone();
+        };
+        info!("Subscribed to vault events on {} ({})", self.host, subscription);
+
+        // The subscription is live before the head is read, so every block
+        // is covered by either the backfill or the stream
+        let head = self.rpc.block_number().await?;
+        if let Some(last) = *synced {
+            self.backfill(last + 1, head, synced, events).await?;
+        }
+        *synced = Some(synced.map_or(head, |last| last.max(head)));
+
+        while let Some(message) = socket.next().await {
+            let text = match message? {
+                Message::Text(text) => text,
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            let notification: Value = serde_json::from_str(&text)?;
+            if notification["method"] != "eth_subscription" {
+                continue;
+            }
+            let log: Log = serde_json::from_value(notification["params"]["result"].clone())?;
+            if log.removed {
+                debug!("Ignoring log removed by a reorg at block {}", log.block_number);
+                continue;
+            }
+            if !self.deliver(&log, synced, events).await? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replay events in `from..=to` that the socket missed
+    async fn backfill(
+        &self,
+        from: u64,
+        to: u64,
+        synced: &mut Option<u64>,
+        events: &mpsc::Sender<VaultEvent>,
+    ) -> Result<()> {
+        if from > to {
+            return Ok(());
+        }
+        info!("Backfilling vault events in blocks {}..={}", from, to);
+        let topics: Vec<B256> = VaultEventKind::ALL.iter().map(|kind| kind.signature()).collect();
+
+        let mut start = from;
+        while start <= to {
+            let end = to.min(start + self.page_size - 1);
+            for log in self.rpc.get_logs(self.vault, &topics, start, end).await? {
+                if !self.deliver(&log, synced, events).await? {
+                    return Ok(());
+                }
+            }
+            *synced = Some(synced.map_or(end, |last| last.max(end)));
+            start = end + 1;
+        }
+        Ok(())
+    }
+
+    /// Pass one log on. Returns `false` once nobody is listening.
+    async fn deliver(&self, log: &Log, synced: &mut Option<u64>, events: &mpsc::Sender<VaultEvent>) -> Result<bool> {
+        let Some(event) = VaultEvent::from_log(log) else {
+            return Err(Error::Decode(format!("unexpected vault log {:?}", log.topics.first())));
+        };
+        *synced = Some(synced.map_or(event.block, |last| last.max(event.block)));
+        Ok(events.send(event).await.is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc::MockRpc;
+    use alloy_primitives::{address, U256, U64};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    const VAULT: Address = address!("00000000000000000000000000000000000000aa");
+    const ALICE: Address = address!("0000000000000000000000000000000000000001");
+    const BOB: Address = address!("0000000000000000000000000000000000000002");
+
+    fn deposit_log(child: Address, block: u64) -> Value {
+        let data = FundsDeposited {
+            child,
+            parent: VAULT,
+            totalAmount: U256::from(10),
+            vaultAmount: U256::from(7),
+            spendingAmount: U256::from(3),
+        }
+        .encode_log_data();
+        json!({ "address": VAULT, "topics": data.topics(), "data": data.data, "blockNumber": U64::from(block) })
+    }
+
+    fn unlock_log(child: Address, block: u64) -> Value {
+        let data = VaultUnlocked {
+            child,
+            amount: U256::from(7),
+            timestamp: U256::from(block),
+        }
+        .encode_log_data();
+        json!({ "address": VAULT, "topics": data.topics(), "data": data.data, "blockNumber": U64::from(block) })
+    }
+
+    /// Accept one websocket connection, confirm its subscription and push `logs`
+    async fn serve_connection(listener: &TcpListener, logs: Vec<Value>) -> tokio_tungstenite::WebSocketStream<tokio::net::TcpStream> {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+
+        let Some(Ok(Message::Text(request))) = socket.next().await else {
+            panic!("expected a subscription request");
+        };
+        let request: Value = serde_json::from_str(&request).unwrap();
+        assert_eq!(request["method"], "eth_subscribe");
+        assert_eq!(request["params"][1]["topics"][0].as_array().unwrap().len(), 4);
+        let reply = json!({ "jsonrpc": "2.0", "id": request["id"], "result": "0x1" });
+        socket.send(Message::Text(reply.to_string())).await.unwrap();
+
+        for log in logs {
+            let notification = json!({
+                "jsonrpc": "2.0",
+                "method": "eth_subscription",
+                "params": { "subscription": "0x1", "result": log },
+            });
+            socket.send(Message::Text(notification.to_string())).await.unwrap();
+        }
+        socket
+    }
+
+    #[tokio::test]
+    async fn streams_and_backfills_after_reconnect() {
+        let head = Arc::new(AtomicU64::new(10));
+        let node_head = head.clone();
+        let node = MockRpc::start(move |method, _| match method {
+            "eth_blockNumber" => Ok(json!(U64::from(node_head.load(Ordering::SeqCst)))),
+            // BOB's unlock happened while the socket was down
+            "eth_getLogs" => Ok(json!([unlock_log(BOB, 15)])),
+            other => panic!("unexpected method {other}"),
+        })
+        .await;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_url = format!("ws://{}", listener.local_addr().unwrap());
+        let rpc = RpcClient::new(reqwest::Client::new(), node.url());
+        let mut events = VaultSubscription::new(ws_url, VAULT, rpc, 1000).spawn();
+
+        let socket = serve_connection(&listener, vec![deposit_log(ALICE, 10)]).await;
+        let event = events.recv().await.unwrap();
+        assert_eq!(
+            event,
+            VaultEvent {
+                kind: VaultEventKind::Deposit,
+                child: ALICE,
+                block: 10
+            }
+        );
+
+        // Drop the connection; the chain moves on before it comes back
+        head.store(20, Ordering::SeqCst);
+        drop(socket);
+        let _socket = serve_connection(&listener, vec![]).await;
+
+        let event = events.recv().await.unwrap();
+        assert_eq!((event.kind, event.child, event.block), (VaultEventKind::Unlock, BOB, 15));
+        let (_, params) = node.calls().into_iter().find(|(method, _)| method == "eth_getLogs").unwrap();
+        assert_eq!((params[0]["fromBlock"].as_str(), params[0]["toBlock"].as_str()), (Some("0xb"), Some("0x14")));
+    }
+}
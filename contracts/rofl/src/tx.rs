@@ -0,0 +1,194 @@
+use crate::error::{Error, Result};
+use crate::rpc::{RpcClient, TransactionReceipt};
+use crate::signer::{LegacyTransaction, Signer};
+use alloy_primitives::{Address, Bytes, B256, U256};
+use log::{debug, info, warn};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::OnceCell;
+use tokio::time::{self, Instant};
+
+/// A submitted transaction whose receipt has not been seen yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingTx {
+    pub hash: B256,
+    pub chain_id: u64,
+    pub to: Address,
+    pub nonce: u64,
+    pub submitted_at: u64,
+}
+
+/// Signs, submits and confirms transactions from the monitor's key
+///
+/// Every transaction is simulated with `eth_estimateGas` first so reverts
+/// surface with their reason before any gas is spent. The chain ID is
+/// fetched once per sender and cached.
+///
+/// A broadcast transaction stays in the pending set until its receipt is
+/// seen, whatever goes wrong while waiting, so it can be persisted and
+/// checked again on later cycles.
+pub struct TxSender {
+    signer: Signer,
+    chain_id: OnceCell<u64>,
+    timeout: Duration,
+    poll_interval: Duration,
+    pending: Mutex<BTreeMap<B256, PendingTx>>,
+}
+
+impl TxSender {
+    pub fn new(signer: Signer, timeout: Duration, poll_interval: Duration) -> Self {
+        Self {
+            signer,
+            chain_id: OnceCell::new(),
+            timeout,
+            poll_interval,
+            pending: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    /// Send a contract call and wait for its receipt
+    pub async fn send(
+        &self,
+        rpc: &RpcClient,
+        to: Address,
+        data: Bytes,
+    ) -> Result<TransactionReceipt> {
+        let hash = self.submit(rpc, to, data.clone()).await?;
+        self.confirm(rpc, hash, to, data).await
+    }
+
+    /// Simulate, sign and broadcast a contract call without waiting for it.
+    /// Once this returns the transaction may be mined at any time.
+    pub async fn submit(&self, rpc: &RpcClient, to: Address, data: Bytes) -> Result<B256> {
+        let from = self.signer.address();
+
+        // Reverts surface here as `Error::Revert` with the revert data
+        let gas = rpc.estimate_gas(from, to, data.clone()).await?;
+        let chain_id = *self.chain_id.get_or_try_init(|| rpc.chain_id()).await?;
+
+        let tx = LegacyTransaction {
+            chain_id,
+            nonce: rpc.pending_nonce(from).await?,
+            gas_price: rpc.gas_price().await?,
+            gas_limit: gas + gas / 5, // 20% headroom over the estimate
+            to,
+            value: U256::ZERO,
+            data,
+        };
+        let raw = self.signer.sign_transaction(&tx)?;
+        let hash = rpc.send_raw_transaction(raw).await?;
+        info!("Submitted tx {} (nonce {}, gas limit {})", hash, tx.nonce, tx.gas_limit);
+        self.pending.lock().unwrap().insert(
+            hash,
+            PendingTx {
+                hash,
+                chain_id,
+                to,
+                nonce: tx.nonce,
+                submitted_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default(),
+            },
+        );
+        Ok(hash)
+    }
+
+    /// Wait for the receipt of a call submitted with `submit`. A mined
+    /// revert comes back as `Error::Revert` when replaying the call names
+    /// it, else as `Error::TxFailed`; any other error leaves the
+    /// transaction pending, as it may still be mined.
+    pub async fn confirm(
+        &self,
+        rpc: &RpcClient,
+        hash: B256,
+        to: Address,
+        data: Bytes,
+    ) -> Result<TransactionReceipt> {
+        let receipt = self.wait_for_receipt(rpc, hash).await?;
+        self.pending.lock().unwrap().remove(&hash);
+        if receipt.succeeded() {
+            info!(
+                "✓ Tx {} confirmed in block {} (gas used {})",
+                hash, receipt.block_number, receipt.gas_used
+            );
+            return Ok(receipt);
+        }
+
+        // Receipts carry no revert data; replay the call on the parent
+        // block's state to recover the reason
+        let block = receipt.block_number.to::<u64>().saturating_sub(1);
+        match rpc.eth_call_at(self.signer.address(), to, data, block).await {
+            Err(e @ Error::Revert { .. }) => Err(e),
+            _ => Err(Error::TxFailed(hash)),
+        }
+    }
+
+    /// Transactions submitted but not yet confirmed
+    pub fn pending(&self) -> Vec<PendingTx> {
+        self.pending.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Re-adopt pending transactions persisted by a previous run
+    pub fn restore_pending(&self, txs: Vec<PendingTx>) {
+        let mut pending = self.pending.lock().unwrap();
+        for tx in txs {
+            pending.insert(tx.hash, tx);
+        }
+    }
+
+    /// Drop pending transactions that have since been mined or dropped.
+    /// Returns the hashes of those that reverted or were dropped, i.e. that
+    /// will never take effect.
+    pub async fn reconcile_pending(&self, rpc: &RpcClient) -> Result<Vec<B256>> {
+        let mut failed = Vec::new();
+        let mut mined_nonce = None;
+        for tx in self.pending() {
+            match rpc.transaction_receipt(tx.hash).await? {
+                Some(receipt) if receipt.succeeded() => info!("✓ Pending tx {} confirmed", tx.hash),
+                Some(_) => {
+                    warn!("Pending tx {} (nonce {}) reverted", tx.hash, tx.nonce);
+                    failed.push(tx.hash);
+                }
+                None => {
+                    // Without a receipt, the tx is only gone for good once
+                    // another one has been mined with its nonce
+                    let mined = match mined_nonce {
+                        Some(nonce) => nonce,
+                        None => *mined_nonce.insert(rpc.nonce(self.signer.address()).await?),
+                    };
+                    if mined <= tx.nonce || rpc.transaction_receipt(tx.hash).await?.is_some() {
+                        continue;
+                    }
+                    warn!("Pending tx {} (nonce {}) was dropped", tx.hash, tx.nonce);
+                    failed.push(tx.hash);
+                }
+            }
+            self.pending.lock().unwrap().remove(&tx.hash);
+        }
+        Ok(failed)
+    }
+
+    async fn wait_for_receipt(
+        &self,
+        rpc: &RpcClient,
+        hash: B256,
+    ) -> Result<TransactionReceipt> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            if let Some(receipt) = rpc.transaction_receipt(hash).await? {
+                return Ok(receipt);
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::TxTimeout(hash));
+            }
+            debug!("Waiting for receipt of {}", hash);
+            time::sleep(self.poll_interval).await;
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use crate::contracts::ScholarFiAgeVerifier::ChildVerified;
+use crate::contracts::ScholarFiVault::{AgeVerificationCompleted, VaultUnlocked};
+use crate::error::{Error, Result};
+use crate::rpc::RpcClient;
+use alloy_primitives::{Address, B256};
+use alloy_sol_types::SolEvent;
+use log::{debug, info};
+use std::collections::BTreeMap;
+
+/// A child seen age-verified on Celo, and whether Oasis knows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Verification {
+    /// Block the verification was seen at; it is only propagated once this
+    /// block is confirmed
+    pub block: u64,
+    /// The `markAgeVerified` transaction, once one was submitted
+    pub marked_tx: Option<B256>,
+}
+
+impl Verification {
+    pub fn seen_at(block: u64) -> Self {
+        Self { block, marked_tx: None }
+    }
+}
+
+/// Indexes age verification on Celo
+///
+/// A child counts as verified when `ScholarFiAgeVerifier` emits
+/// `ChildVerified`, or the vault emits `AgeVerificationCompleted` or
+/// `VaultUnlocked` (which requires verification). Like deposits, only
+/// confirmed blocks are read, since `markAgeVerified` cannot be undone.
+/// Both contracts are scanned from the vault's deployment block; anything
+/// missed before it is caught by reconciliation.
+pub struct VerificationWatcher {
+    vault: Address,
+    verifier: Option<Address>,
+    next_block: u64,
+    page_size: u64,
+    confirmations: u64,
+}
+
+impl VerificationWatcher {
+    pub fn new(
+        vault: Address,
+        verifier: Option<Address>,
+        start_block: u64,
+        page_size: u64,
+        confirmations: u64,
+    ) -> Self {
+        Self {
+            vault,
+            verifier,
+            next_block: start_block,
+            page_size: page_size.max(1),
+            confirmations,
+        }
+    }
+
+    /// Resume from a persisted checkpoint
+    pub fn restore(&mut self, next_block: u64) {
+        self.next_block = self.next_block.max(next_block);
+    }
+
+    /// First block not scanned yet
+    pub fn next_block(&self) -> u64 {
+        self.next_block
+    }
+
+    /// Whether `block` is deep enough below `head` to act on
+    pub fn is_confirmed(&self, block: u64, head: u64) -> bool {
+        block + self.confirmations <= head + 1
+    }
+
+    /// Add children verified in newly confirmed blocks to `verifications`.
+    /// Returns the number added.
+    pub async fn scan(&mut self, rpc: &RpcClient, verifications: &mut BTreeMap<Address, Verification>) -> Result<usize> {
+        let head = rpc.block_number().await?;
+        let Some(confirmed) = (head + 1).checked_sub(self.confirmations) else {
+            return Ok(0);
+        };
+        let mut found = 0;
+
+        while self.next_block <= confirmed {
+            let to_block = confirmed.min(self.next_block + self.page_size - 1);
+            debug!("Indexing age verification in blocks {}..={}", self.next_block, to_block);
+
+            let mut logs = rpc
+                .get_logs(
+                    self.vault,
+                    &[AgeVerificationCompleted::SIGNATURE_HASH, VaultUnlocked::SIGNATURE_HASH],
+                    self.next_block,
+                    to_block,
+                )
+                .await?;
+            if let Some(verifier) = self.verifier {
+                logs.extend(
+                    rpc.get_logs(verifier, &[ChildVerified::SIGNATURE_HASH], self.next_block, to_block)
+                        .await?,
+                );
+            }
+
+            for log in logs {
+                // The child is the first indexed argument of all three events
+                let child = log
+                    .topics
+                    .get(1)
+                    .map(|topic| Address::from_word(*topic))
+                    .ok_or_else(|| Error::Decode("verification log without a child topic".to_string()))?;
+                if verifications.contains_key(&child) {
+                    continue;
+                }
+                info!("Child {} age-verified on Celo at block {}", child, log.block_number);
+                verifications.insert(child, Verification::seen_at(log.block_number.to()));
+                found += 1;
+            }
+
+            self.next_block = to_block + 1;
+        }
+
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contracts::ScholarFiAgeVerifier::GenericDiscloseOutputV2;
+    use crate::contracts::ScholarFiVault;
+    use crate::mock_rpc::MockChain;
+    use alloy_primitives::{address, U256};
+    use alloy_sol_types::SolEvent;
+    use serde_json::json;
+
+    const VAULT: Address = address!("00000000000000000000000000000000000000aa");
+    const VERIFIER: Address = address!("00000000000000000000000000000000000000ee");
+    const ALICE: Address = address!("0000000000000000000000000000000000000001");
+    const BOB: Address = address!("0000000000000000000000000000000000000002");
+    const CAROL: Address = address!("0000000000000000000000000000000000000003");
+
+    #[tokio::test]
+    async fn scans_vault_and_verifier_events() {
+        let chain = MockChain::start(12).await;
+        let unlocked = ScholarFiVault::VaultUnlocked {
+            child: ALICE,
+            amount: U256::from(1u64),
+            timestamp: U256::from(1_700_000_000u64),
+        }
+        .encode_log_data();
+        chain.add_log(2, json!({ "address": VAULT, "topics": unlocked.topics(), "data": unlocked.data }));
+        let verified = ChildVerified {
+            childAddress: BOB,
+            parentAddress: VAULT,
+            timestamp: U256::from(1_700_000_000u64),
+            output: GenericDiscloseOutputV2 {
+                attestationId: B256::ZERO,
+                userIdentifier: U256::ZERO,
+                nullifier: U256::ZERO,
+                forbiddenCountriesListPacked: [U256::ZERO; 4],
+                issuingState: String::new(),
+                name: Vec::new(),
+                idNumber: String::new(),
+                nationality: String::new(),
+                dateOfBirth: String::new(),
+                gender: String::new(),
+                expiryDate: String::new(),
+                olderThan: U256::from(18u64),
+                ofac: [false; 3],
+            },
+        }
+        .encode_log_data();
+        chain.add_log(6, json!({ "address": VERIFIER, "topics": verified.topics(), "data": verified.data }));
+        // Not confirmed yet
+        let later = ScholarFiVault::VaultUnlocked {
+            child: CAROL,
+            amount: U256::from(1u64),
+            timestamp: U256::from(1_700_000_000u64),
+        }
+        .encode_log_data();
+        chain.add_log(11, json!({ "address": VAULT, "topics": later.topics(), "data": later.data }));
+        let rpc = RpcClient::new(reqwest::Client::new(), chain.url());
+        let mut watcher = VerificationWatcher::new(VAULT, Some(VERIFIER), 0, 4, 3);
+        let mut verifications = BTreeMap::new();
+
+        assert_eq!(watcher.scan(&rpc, &mut verifications).await.unwrap(), 2);
+        assert_eq!(
+            verifications,
+            BTreeMap::from([(ALICE, Verification::seen_at(2)), (BOB, Verification::seen_at(6))])
+        );
+        assert_eq!(watcher.next_block(), 11);
+        assert!(watcher.is_confirmed(10, 12));
+        assert!(!watcher.is_confirmed(11, 12));
+    }
+}